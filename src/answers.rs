@@ -0,0 +1,332 @@
+use std::{fs, path::Path};
+
+use anyhow::Context as _;
+use indexmap::IndexMap;
+use openapiv3::{OpenAPI, ParameterSchemaOrContent};
+use oreq::{
+    fake::Faker,
+    schema::{
+        describe::deprecated_fields, error::SchemaError, operation::OperationItem,
+        reference::ReferenceOrExt,
+    },
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    interpolate,
+    req::{ParamsValue, RequestInit},
+};
+
+/// Values of the parameters and the body, keyed by their location.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Answers {
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub path: IndexMap<String, Value>,
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub query: IndexMap<String, Value>,
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub header: IndexMap<String, Value>,
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub cookie: IndexMap<String, Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<Value>,
+}
+
+impl Answers {
+    /// The parameters of `path`, `query`, `header` or `cookie`.
+    pub fn params(&self, location: &str) -> &IndexMap<String, Value> {
+        match location {
+            "path" => &self.path,
+            "query" => &self.query,
+            "header" => &self.header,
+            "cookie" => &self.cookie,
+            _ => unreachable!("unknown parameter location: {}", location),
+        }
+    }
+
+    pub fn params_mut(&mut self, location: &str) -> &mut IndexMap<String, Value> {
+        match location {
+            "path" => &mut self.path,
+            "query" => &mut self.query,
+            "header" => &mut self.header,
+            "cookie" => &mut self.cookie,
+            _ => unreachable!("unknown parameter location: {}", location),
+        }
+    }
+
+    /// Fills the parameters and the body which are not given with fake values.
+    /// A given body is completed with the properties it misses.
+    pub fn fake(
+        &mut self,
+        api: &OpenAPI,
+        item: &OperationItem,
+        faker: &mut Faker,
+    ) -> Result<(), SchemaError> {
+        for (location, parameter) in item.parameters(api)? {
+            if self.params(location).contains_key(&parameter.name)
+                || !faker.includes(parameter.required, parameter.deprecated == Some(true))
+            {
+                continue;
+            }
+            // Content parameters are left to the prompts.
+            if let ParameterSchemaOrContent::Schema(schema) = &parameter.format {
+                let value = faker.value(api, schema.item(api)?, None)?;
+                self.params_mut(location)
+                    .insert(parameter.name.clone(), value);
+            }
+        }
+
+        let body = item
+            .operation
+            .request_body
+            .as_ref()
+            .map(|x| x.item(api))
+            .transpose()?;
+        let schema = body
+            .and_then(|x| x.content.get("application/json"))
+            .and_then(|x| x.schema.as_ref());
+        if let (Some(body), Some(schema)) = (body, schema) {
+            if self.body.is_some() || faker.includes(body.required, false) {
+                self.body = Some(faker.value(api, schema.item(api)?, self.body.as_ref())?);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Escapes every value, for answers generated from the schema.
+    pub fn escape(&mut self) {
+        for location in ["path", "query", "header", "cookie"] {
+            for value in self.params_mut(location).values_mut() {
+                *value = interpolate::escape_value(value);
+            }
+        }
+        self.body = self.body.as_ref().map(interpolate::escape_value);
+    }
+
+    /// Reads answers from a JSON or YAML file.
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        serde_yaml::from_str::<Self>(&content)
+            .with_context(|| format!("Failed to parse {}", path.display()))
+    }
+}
+
+/// The operation selected in the prompts and the answers for it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Answered {
+    pub method: String,
+    pub path: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub operation_id: Option<String>,
+    pub answers: Answers,
+}
+
+impl Answered {
+    /// Describes the deprecated operation, parameters and body properties in use.
+    pub fn deprecations(&self, api: &OpenAPI) -> Result<Vec<String>, SchemaError> {
+        let Some(item) = OperationItem::find(api, &self.method, &self.path)? else {
+            return Ok(Vec::new());
+        };
+
+        let mut result = Vec::new();
+        if item.operation.deprecated {
+            result.push(format!("{} {} is deprecated", self.method, self.path));
+        }
+        let answers = &self.answers;
+        for (location, parameter) in item.parameters(api)? {
+            let values = match location {
+                "path" => &answers.path,
+                "query" => &answers.query,
+                "header" => &answers.header,
+                _ => &answers.cookie,
+            };
+            if parameter.deprecated == Some(true) && values.contains_key(&parameter.name) {
+                result.push(format!(
+                    "{} parameter '{}' is deprecated",
+                    location, parameter.name
+                ));
+            }
+        }
+        let schema = item
+            .operation
+            .request_body
+            .as_ref()
+            .map(|x| x.item(api))
+            .transpose()?
+            .and_then(|x| x.content.get("application/json"))
+            .and_then(|x| x.schema.as_ref());
+        if let (Some(schema), Some(body)) = (schema, &answers.body) {
+            let fields = deprecated_fields(api, schema.item(api)?, body, "body")?;
+            result.extend(fields.into_iter().map(|x| format!("'{}' is deprecated", x)));
+        }
+
+        Ok(result)
+    }
+
+    /// Builds the request. Array query parameters are repeated per item unless
+    /// the parameter sets `explode: false`.
+    pub fn request(&self, api: &OpenAPI) -> Result<RequestInit, SchemaError> {
+        let answers = &self.answers;
+        let item = OperationItem::find(api, &self.method, &self.path)?;
+        let parameters = match &item {
+            Some(item) => item.parameters(api)?,
+            None => Vec::new(),
+        };
+        let explode = |name: &str| {
+            parameters
+                .iter()
+                .find(|(location, x)| *location == "query" && x.name == name)
+                .map(|(_, x)| x.explode.unwrap_or(true))
+                .unwrap_or(false)
+        };
+
+        Ok(RequestInit {
+            method: self.method.clone(),
+            base: String::new(),
+            path: answers
+                .path
+                .iter()
+                .map(|(k, v)| (k, ParamsValue::from(v.clone())))
+                .fold(interpolate::escape(&self.path), |acc, (name, value)| {
+                    acc.replace(&format!("{{{}}}", name), &value.to_string())
+                }),
+            query: answers
+                .query
+                .iter()
+                .flat_map(|(k, v)| match v {
+                    Value::Array(items) if explode(k) => items
+                        .iter()
+                        .map(|x| (k.clone(), Some(x.clone())))
+                        .collect::<Vec<_>>(),
+                    _ => vec![(k.clone(), Some(v.clone()))],
+                })
+                .collect(),
+            header: answers
+                .header
+                .iter()
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect(),
+            cookie: answers
+                .cookie
+                .iter()
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect(),
+            body: answers.body.clone(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn api() -> OpenAPI {
+        serde_yaml::from_str(indoc::indoc! {r#"
+            openapi: 3.0.0
+            info: {title: pets, version: "1"}
+            paths:
+              /pets/{id}:
+                post:
+                  operationId: updatePet
+                  parameters:
+                    - {name: id, in: path, required: true, schema: {type: integer}}
+                    - {name: tag, in: query, schema: {type: array, items: {type: string}}}
+                    - {name: fields, in: query, explode: false, schema: {type: array, items: {type: string}}}
+                  requestBody:
+                    required: true
+                    content:
+                      application/json:
+                        schema:
+                          type: object
+                          required: [name, kind]
+                          properties:
+                            name: {type: string}
+                            kind: {type: string, enum: [cat, dog]}
+                  responses: {"200": {description: ok}}
+        "#})
+        .unwrap()
+    }
+
+    #[test]
+    fn load_json_and_yaml() {
+        let dir = std::env::temp_dir();
+        let yaml = dir.join(format!("oreq-answers-{}.yaml", std::process::id()));
+        let json = dir.join(format!("oreq-answers-{}.json", std::process::id()));
+        fs::write(&yaml, "path:\n  id: 7\nbody:\n  name: Tama\n").unwrap();
+        fs::write(
+            &json,
+            r#"{"query": {"tag": ["a"]}, "body": {"name": "Tama"}}"#,
+        )
+        .unwrap();
+        let loaded = (Answers::load(&yaml), Answers::load(&json));
+        fs::remove_file(&yaml).unwrap();
+        fs::remove_file(&json).unwrap();
+
+        let (yaml, json) = (loaded.0.unwrap(), loaded.1.unwrap());
+        assert_eq!(yaml.path["id"], json!(7));
+        assert_eq!(yaml.body, Some(json!({"name": "Tama"})));
+        assert_eq!(json.query["tag"], json!(["a"]));
+        assert_eq!(json.body, yaml.body);
+
+        let missing = Answers::load(&dir.join("oreq-answers-missing.yaml")).unwrap_err();
+        assert!(missing.to_string().starts_with("Failed to read"));
+    }
+
+    #[test]
+    fn fake_completes_given_values() {
+        let api = api();
+        let item = OperationItem::find_by_id(&api, "updatePet")
+            .unwrap()
+            .unwrap();
+        let mut answers = Answers {
+            path: IndexMap::from([("id".to_owned(), json!(7))]),
+            body: Some(json!({"name": "Tama"})),
+            ..Default::default()
+        };
+        let mut faker = Faker::new(Some(0));
+        faker.with_optional(false);
+        answers.fake(&api, &item, &mut faker).unwrap();
+
+        assert_eq!(answers.path["id"], json!(7));
+        assert!(answers.query.is_empty());
+        let body = answers.body.unwrap();
+        assert_eq!(body["name"], json!("Tama"));
+        assert!(["cat", "dog"].contains(&body["kind"].as_str().unwrap()));
+    }
+
+    #[test]
+    fn request_from_answers() {
+        let mut answered = Answered {
+            method: "POST".to_owned(),
+            path: "/pets/{id}".to_owned(),
+            operation_id: Some("updatePet".to_owned()),
+            answers: Answers {
+                path: IndexMap::from([("id".to_owned(), json!(7))]),
+                query: IndexMap::from([
+                    ("tag".to_owned(), json!(["a", "b"])),
+                    ("fields".to_owned(), json!(["name", "kind"])),
+                ]),
+                body: Some(json!({"name": "$(id)"})),
+                ..Default::default()
+            },
+        };
+        answered.answers.escape();
+
+        let request = answered.request(&api()).unwrap();
+        assert_eq!(request.path, "/pets/7");
+        assert_eq!(
+            request.query,
+            [
+                ("tag".to_owned(), Some(json!("a"))),
+                ("tag".to_owned(), Some(json!("b"))),
+                ("fields".to_owned(), Some(json!(["name", "kind"]))),
+            ]
+        );
+        assert_eq!(request.body, Some(json!({"name": "\\$(id)"})));
+    }
+}
@@ -0,0 +1,776 @@
+use std::fmt::Display;
+
+use openapiv3::{OpenAPI, ParameterSchemaOrContent, ReferenceOr, Schema, SchemaKind, Type};
+use oreq::{
+    fake::Faker,
+    prompts::{array, number, string},
+    schema::{error::SchemaError, operation::OperationItem, pattern, reference::ReferenceOrExt},
+    validate::{self, Violation},
+};
+use serde_json::{json, Value};
+
+use crate::answers::Answers;
+
+/// Properties nested deeper than this get no cases.
+const MAX_DEPTH: usize = 8;
+/// Tries to generate an item which differs from the others.
+const TRIES: usize = 16;
+
+/// Whether the API is expected to accept the request of a case.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expect {
+    Valid,
+    /// Rejected for the keyword of the constraint which is broken.
+    Invalid(&'static str),
+}
+
+impl Display for Expect {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Expect::Valid => write!(f, "valid"),
+            Expect::Invalid(keyword) => write!(f, "invalid ({})", keyword),
+        }
+    }
+}
+
+impl Expect {
+    /// Whether the violations are the expected ones: none for a valid request,
+    /// or only violations of the keyword at the field for an invalid one.
+    fn matches(&self, violations: &[Violation], field: &str) -> bool {
+        match self {
+            Expect::Valid => violations.is_empty(),
+            Expect::Invalid(keyword) => {
+                !violations.is_empty()
+                    && violations
+                        .iter()
+                        .all(|x| x.path == field && x.keywords().contains(keyword))
+            }
+        }
+    }
+}
+
+/// A request at or past the boundary of a constraint, and the outcome expected from it.
+pub struct Case {
+    /// The field and the boundary, e.g. `body.name:maxLength+1`.
+    pub name: String,
+    pub expect: Expect,
+    pub answers: Answers,
+}
+
+#[derive(Debug, Clone)]
+enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+/// Derives boundary and negative cases from the constraints of the parameters and
+/// the body of an operation. Each case changes a single field of a valid request.
+/// The parameters in `given`, e.g. headers of the environment, are kept as they are.
+pub fn cases(
+    api: &OpenAPI,
+    item: &OperationItem,
+    faker: &mut Faker,
+    given: &Answers,
+) -> Result<Vec<Case>, SchemaError> {
+    let mut base = given.clone();
+    faker.with_optional(false);
+    base.fake(api, item, faker)?;
+
+    let mut deriver = Deriver {
+        api,
+        faker,
+        cases: vec![Case {
+            name: "valid".to_owned(),
+            expect: Expect::Valid,
+            answers: base.clone(),
+        }],
+        references: Vec::new(),
+    };
+
+    for (location, parameter) in item.parameters(api)? {
+        let ParameterSchemaOrContent::Schema(schema) = &parameter.format else {
+            continue;
+        };
+        if given.params(location).contains_key(&parameter.name) {
+            continue;
+        }
+        let schema = schema.item(api)?;
+        let field = format!("{}.{}", location, parameter.name);
+        // A path without one of its parameters is not a request of the operation.
+        if parameter.required && location != "path" {
+            let mut answers = base.clone();
+            answers.params_mut(location).shift_remove(&parameter.name);
+            deriver.push(&field, "missing", Expect::Invalid("required"), answers);
+        }
+
+        let value = match base.params(location).get(&parameter.name) {
+            Some(value) => value.clone(),
+            None => deriver.faker.value(api, schema, None)?,
+        };
+        for (label, value, expect) in deriver.variants(schema, &value)? {
+            if !expect.matches(&validate::validate(api, schema, &value, &field)?, &field) {
+                continue;
+            }
+            let mut answers = base.clone();
+            answers
+                .params_mut(location)
+                .insert(parameter.name.clone(), value);
+            deriver.push(&field, &label, expect, answers);
+        }
+    }
+
+    let schema = item
+        .operation
+        .request_body
+        .as_ref()
+        .map(|x| x.item(api))
+        .transpose()?
+        .and_then(|x| x.content.get("application/json"))
+        .and_then(|x| x.schema.as_ref());
+    if let (Some(schema), Some(body)) = (schema, &base.body) {
+        let schema = schema.item(api)?;
+        deriver.body(schema, schema, body, &mut Vec::new(), &base)?;
+    }
+
+    Ok(deriver.cases)
+}
+
+struct Deriver<'a> {
+    api: &'a OpenAPI,
+    faker: &'a mut Faker,
+    cases: Vec<Case>,
+    /// The references on the way to the current field. A recursive schema is walked once.
+    references: Vec<String>,
+}
+
+impl Deriver<'_> {
+    fn push(&mut self, field: &str, label: &str, expect: Expect, answers: Answers) {
+        self.cases.push(Case {
+            name: format!("{}:{}", field, label),
+            expect,
+            answers,
+        });
+    }
+
+    /// Walks the body, adding the cases of each field at `path`. `body` is the whole
+    /// body, which has the optional fields on the way filled in.
+    fn body(
+        &mut self,
+        root: &Schema,
+        schema: &Schema,
+        body: &Value,
+        path: &mut Vec<Segment>,
+        base: &Answers,
+    ) -> Result<(), SchemaError> {
+        if path.len() > MAX_DEPTH {
+            return Ok(());
+        }
+        let field = field_name(path);
+        let value = get(body, path).cloned().unwrap_or(Value::Null);
+        for (label, value, expect) in self.variants(schema, &value)? {
+            let mut changed = body.clone();
+            set(&mut changed, path, Some(value));
+            self.push_body(root, &field, &label, expect, changed, base)?;
+        }
+
+        match &schema.schema_kind {
+            SchemaKind::Type(Type::Object(object)) => {
+                for (key, property) in &object.properties {
+                    let property = property.clone().unbox();
+                    let reference = reference(&property);
+                    if reference.is_some_and(|x| self.references.contains(x)) {
+                        continue;
+                    }
+                    let property = property.item(self.api)?;
+                    if property.schema_data.read_only {
+                        continue;
+                    }
+                    path.push(Segment::Key(key.clone()));
+                    let field = field_name(path);
+                    let body = match get(body, path) {
+                        Some(_) if object.required.contains(key) => {
+                            let mut changed = body.clone();
+                            set(&mut changed, path, None);
+                            self.push_body(
+                                root,
+                                &field,
+                                "missing",
+                                Expect::Invalid("required"),
+                                changed,
+                                base,
+                            )?;
+                            body.clone()
+                        }
+                        Some(_) => body.clone(),
+                        None => {
+                            let mut filled = body.clone();
+                            let value = self.faker.value(self.api, property, None)?;
+                            set(&mut filled, path, Some(value));
+                            filled
+                        }
+                    };
+                    self.nested(root, property, &body, path, base, reference)?;
+                    path.pop();
+                }
+            }
+            SchemaKind::Type(Type::Array(array)) => {
+                if let Some(items) = &array.items {
+                    let items = items.clone().unbox();
+                    let reference = reference(&items);
+                    if reference.is_some_and(|x| self.references.contains(x)) {
+                        return Ok(());
+                    }
+                    let items = items.item(self.api)?;
+                    path.push(Segment::Index(0));
+                    let body = match get(body, path) {
+                        Some(_) => body.clone(),
+                        None => {
+                            let mut filled = body.clone();
+                            let value = self.faker.value(self.api, items, None)?;
+                            set(&mut filled, path, Some(value));
+                            filled
+                        }
+                    };
+                    self.nested(root, items, &body, path, base, reference)?;
+                    path.pop();
+                }
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn nested(
+        &mut self,
+        root: &Schema,
+        schema: &Schema,
+        body: &Value,
+        path: &mut Vec<Segment>,
+        base: &Answers,
+        reference: Option<&String>,
+    ) -> Result<(), SchemaError> {
+        self.references.extend(reference.cloned());
+        let result = self.body(root, schema, body, path, base);
+        if reference.is_some() {
+            self.references.pop();
+        }
+        result
+    }
+
+    /// Adds the case when validating the body gives the expected outcome. A boundary
+    /// which breaks another constraint too, e.g. a pattern, would not test what it is named after.
+    fn push_body(
+        &mut self,
+        root: &Schema,
+        field: &str,
+        label: &str,
+        expect: Expect,
+        body: Value,
+        base: &Answers,
+    ) -> Result<(), SchemaError> {
+        let violations = validate::validate(self.api, root, &body, "body")?;
+        if expect.matches(&violations, field) {
+            let mut answers = base.clone();
+            answers.body = Some(body);
+            self.push(field, label, expect, answers);
+        }
+        Ok(())
+    }
+
+    /// The values at and past the boundaries of the constraints of the schema.
+    fn variants(
+        &mut self,
+        schema: &Schema,
+        value: &Value,
+    ) -> Result<Vec<(String, Value, Expect)>, SchemaError> {
+        Ok(match &schema.schema_kind {
+            SchemaKind::Type(Type::String(x)) => {
+                string_variants(&string::PromptOption::from(x.clone()), value)
+            }
+            SchemaKind::Type(Type::Integer(x)) => {
+                integer_variants(&number::PromptOption::<i128>::from(x.clone()), value)
+            }
+            SchemaKind::Type(Type::Number(x)) => {
+                number_variants(&number::PromptOption::<f64>::from(x.clone()), value)
+            }
+            SchemaKind::Type(Type::Array(x)) => {
+                let option = array::PromptOption::new(x.clone());
+                let items = match &x.items {
+                    Some(items) => Some(items.clone().unbox().item(self.api)?.clone()),
+                    None => None,
+                };
+                let mut values = value.as_array().cloned().unwrap_or_default();
+                let mut variants = Vec::new();
+                let mut sized = |len: usize| -> Result<Value, SchemaError> {
+                    for _ in 0..TRIES {
+                        if values.len() >= len {
+                            break;
+                        }
+                        let item = match &items {
+                            Some(items) => self.faker.value(self.api, items, None)?,
+                            None => json!(values.len()),
+                        };
+                        if !values.contains(&item) {
+                            values.push(item);
+                        }
+                    }
+                    Ok(json!(values.iter().take(len).collect::<Vec<_>>()))
+                };
+                if let Some(min) = option.min_items() {
+                    variants.push(("minItems".to_owned(), sized(min)?, Expect::Valid));
+                    if min > 0 {
+                        variants.push((
+                            "minItems-1".to_owned(),
+                            sized(min - 1)?,
+                            Expect::Invalid("minItems"),
+                        ));
+                    }
+                }
+                if let Some(max) = option.max_items() {
+                    variants.push(("maxItems".to_owned(), sized(max)?, Expect::Valid));
+                    variants.push((
+                        "maxItems+1".to_owned(),
+                        sized(max + 1)?,
+                        Expect::Invalid("maxItems"),
+                    ));
+                }
+                if option.unique_items() {
+                    if let Value::Array(items) = sized(1)? {
+                        let duplicated = items.iter().chain(&items).cloned().collect::<Vec<_>>();
+                        variants.push((
+                            "duplicate".to_owned(),
+                            json!(duplicated),
+                            Expect::Invalid("uniqueItems"),
+                        ));
+                    }
+                }
+                variants
+            }
+            _ => Vec::new(),
+        })
+    }
+}
+
+fn string_variants(option: &string::PromptOption, value: &Value) -> Vec<(String, Value, Expect)> {
+    let value = value.as_str().unwrap_or_default();
+    let mut variants = Vec::new();
+
+    let enumeration = option.enumeration().collect::<Vec<_>>();
+    if !enumeration.is_empty() {
+        let outside = (0..)
+            .map(|x| format!("invalid{}", x))
+            .find(|x| !enumeration.contains(&x))
+            .unwrap_or_default();
+        variants.push((
+            "not-enum".to_owned(),
+            json!(outside),
+            Expect::Invalid("enum"),
+        ));
+    }
+    if let Some(min) = option.min_length() {
+        variants.push((
+            "minLength".to_owned(),
+            json!(resize(value, min)),
+            Expect::Valid,
+        ));
+        if min > 0 {
+            variants.push((
+                "minLength-1".to_owned(),
+                json!(resize(value, min - 1)),
+                Expect::Invalid("minLength"),
+            ));
+        }
+    }
+    if let Some(max) = option.max_length() {
+        variants.push((
+            "maxLength".to_owned(),
+            json!(resize(value, max)),
+            Expect::Valid,
+        ));
+        variants.push((
+            "maxLength+1".to_owned(),
+            json!(resize(value, max + 1)),
+            Expect::Invalid("maxLength"),
+        ));
+    }
+    if let Some(Ok(regex)) = option.pattern().map(pattern::compile) {
+        let unmatched = ["", "!", " ", "0", "a", "not matching"]
+            .into_iter()
+            .find(|x| !regex.is_match(x));
+        if let Some(unmatched) = unmatched {
+            variants.push((
+                "not-pattern".to_owned(),
+                json!(unmatched),
+                Expect::Invalid("pattern"),
+            ));
+        }
+    }
+    if let Some(format) = option.format() {
+        let invalid = ["invalid", "-invalid-"]
+            .into_iter()
+            .find(|x| format.check(x).is_err());
+        if let Some(invalid) = invalid {
+            variants.push((
+                "not-format".to_owned(),
+                json!(invalid),
+                Expect::Invalid("format"),
+            ));
+        }
+    }
+
+    variants
+}
+
+fn integer_variants(
+    option: &number::PromptOption<i128>,
+    value: &Value,
+) -> Vec<(String, Value, Expect)> {
+    let mut variants: Vec<(String, i128, Expect)> = Vec::new();
+    let enumeration = option.enumeration().cloned().collect::<Vec<_>>();
+    if let Some(max) = enumeration.iter().max() {
+        variants.push(("not-enum".to_owned(), max + 1, Expect::Invalid("enum")));
+    }
+
+    match option.minimum() {
+        Some((min, true)) => {
+            variants.push(("exclusiveMinimum+1".to_owned(), min + 1, Expect::Valid));
+            variants.push((
+                "exclusiveMinimum".to_owned(),
+                min,
+                Expect::Invalid("minimum"),
+            ));
+        }
+        Some((min, false)) => {
+            variants.push(("minimum".to_owned(), min, Expect::Valid));
+            variants.push(("minimum-1".to_owned(), min - 1, Expect::Invalid("minimum")));
+        }
+        None => {
+            if let Some((min, _, name)) = option.format() {
+                variants.push((
+                    format!("{}-min-1", name),
+                    min - 1,
+                    Expect::Invalid("format"),
+                ));
+            }
+        }
+    }
+    match option.maximum() {
+        Some((max, true)) => {
+            variants.push(("exclusiveMaximum-1".to_owned(), max - 1, Expect::Valid));
+            variants.push((
+                "exclusiveMaximum".to_owned(),
+                max,
+                Expect::Invalid("maximum"),
+            ));
+        }
+        Some((max, false)) => {
+            variants.push(("maximum".to_owned(), max, Expect::Valid));
+            variants.push(("maximum+1".to_owned(), max + 1, Expect::Invalid("maximum")));
+        }
+        None => {
+            if let Some((_, max, name)) = option.format() {
+                variants.push((
+                    format!("{}-max+1", name),
+                    max + 1,
+                    Expect::Invalid("format"),
+                ));
+            }
+        }
+    }
+    if let (Some(step), Some(value)) = (option.multiple_of().filter(|x| *x > 1), value.as_i64()) {
+        variants.push((
+            "not-multipleOf".to_owned(),
+            value as i128 + 1,
+            Expect::Invalid("multipleOf"),
+        ));
+        variants.push(("multipleOf".to_owned(), value as i128 + step, Expect::Valid));
+    }
+
+    // Integers beyond `i64` and `u64` would be rounded, so their cases are left out.
+    variants
+        .into_iter()
+        .filter_map(|(label, value, expect)| Some((label, exact(value)?, expect)))
+        .collect()
+}
+
+fn number_variants(
+    option: &number::PromptOption<f64>,
+    value: &Value,
+) -> Vec<(String, Value, Expect)> {
+    let mut variants = Vec::new();
+    let enumeration = option.enumeration().cloned().collect::<Vec<_>>();
+    if let Some(max) = enumeration.iter().copied().reduce(f64::max) {
+        variants.push((
+            "not-enum".to_owned(),
+            json!(max + 1.0),
+            Expect::Invalid("enum"),
+        ));
+    }
+
+    // Just past a boundary is a step away, the multiple or one.
+    let step = option.multiple_of().filter(|x| *x > 0.0).unwrap_or(1.0);
+    match option.minimum() {
+        Some((min, true)) => {
+            variants.push((
+                "exclusiveMinimum".to_owned(),
+                json!(min),
+                Expect::Invalid("minimum"),
+            ));
+        }
+        Some((min, false)) => {
+            variants.push(("minimum".to_owned(), json!(min), Expect::Valid));
+            variants.push((
+                "minimum-step".to_owned(),
+                json!(min - step),
+                Expect::Invalid("minimum"),
+            ));
+        }
+        None => {}
+    }
+    match option.maximum() {
+        Some((max, true)) => {
+            variants.push((
+                "exclusiveMaximum".to_owned(),
+                json!(max),
+                Expect::Invalid("maximum"),
+            ));
+        }
+        Some((max, false)) => {
+            variants.push(("maximum".to_owned(), json!(max), Expect::Valid));
+            variants.push((
+                "maximum+step".to_owned(),
+                json!(max + step),
+                Expect::Invalid("maximum"),
+            ));
+        }
+        None => {}
+    }
+    if let Some((_, max, name)) = option.format().filter(|(_, max, _)| *max < f64::MAX) {
+        variants.push((
+            format!("{}-max*2", name),
+            json!(max * 2.0),
+            Expect::Invalid("format"),
+        ));
+    }
+    if let (Some(step), Some(value)) = (option.multiple_of().filter(|x| *x > 0.0), value.as_f64()) {
+        variants.push((
+            "not-multipleOf".to_owned(),
+            json!(value + step / 2.0),
+            Expect::Invalid("multipleOf"),
+        ));
+    }
+
+    variants
+}
+
+fn reference(schema: &ReferenceOr<Schema>) -> Option<&String> {
+    match schema {
+        ReferenceOr::Reference { reference } => Some(reference),
+        ReferenceOr::Item(_) => None,
+    }
+}
+
+/// The integer as JSON, unless it is beyond `i64` and `u64`.
+fn exact(value: i128) -> Option<Value> {
+    i64::try_from(value)
+        .map(Value::from)
+        .or(u64::try_from(value).map(Value::from))
+        .ok()
+}
+
+/// The string cut or repeated to `len` characters.
+fn resize(value: &str, len: usize) -> String {
+    let value = if value.is_empty() { "a" } else { value };
+    value.chars().cycle().take(len).collect()
+}
+
+/// `body.items[0].name`
+fn field_name(path: &[Segment]) -> String {
+    path.iter().fold("body".to_owned(), |acc, x| match x {
+        Segment::Key(key) => format!("{}.{}", acc, key),
+        Segment::Index(idx) => format!("{}[{}]", acc, idx),
+    })
+}
+
+fn get<'a>(value: &'a Value, path: &[Segment]) -> Option<&'a Value> {
+    path.iter().try_fold(value, |value, x| match x {
+        Segment::Key(key) => value.get(key),
+        Segment::Index(idx) => value.get(idx),
+    })
+}
+
+/// Sets or removes the value at `path`, creating the objects and the arrays on the way.
+fn set(target: &mut Value, path: &[Segment], value: Option<Value>) {
+    let Some((segment, rest)) = path.split_first() else {
+        if let Some(value) = value {
+            *target = value;
+        }
+        return;
+    };
+
+    match segment {
+        Segment::Key(key) => {
+            if !target.is_object() {
+                *target = json!({});
+            }
+            let Some(map) = target.as_object_mut() else {
+                return;
+            };
+            match (rest.is_empty(), value) {
+                (true, Some(value)) => {
+                    map.insert(key.clone(), value);
+                }
+                (true, None) => {
+                    map.remove(key);
+                }
+                (false, value) => set(map.entry(key.clone()).or_insert(Value::Null), rest, value),
+            }
+        }
+        Segment::Index(idx) => {
+            if !target.is_array() {
+                *target = json!([]);
+            }
+            let Some(items) = target.as_array_mut() else {
+                return;
+            };
+            if *idx >= items.len() {
+                if value.is_none() {
+                    return;
+                }
+                items.push(Value::Null);
+            }
+            let idx = (*idx).min(items.len() - 1);
+            match (rest.is_empty(), value) {
+                (true, Some(value)) => items[idx] = value,
+                (true, None) => {
+                    items.remove(idx);
+                }
+                (false, value) => set(&mut items[idx], rest, value),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indexmap::IndexMap;
+
+    use super::*;
+
+    fn api() -> OpenAPI {
+        serde_yaml::from_str(indoc::indoc! {r#"
+            openapi: 3.0.0
+            info: {title: pets, version: "1"}
+            paths:
+              /pets/{id}:
+                put:
+                  operationId: updatePet
+                  parameters:
+                    - {name: id, in: path, required: true, schema: {type: integer, format: int32, minimum: 1}}
+                    - {name: limit, in: query, required: true, schema: {type: integer, maximum: 100}}
+                    - {name: X-Trace, in: header, schema: {type: string, maxLength: 8}}
+                  requestBody:
+                    required: true
+                    content:
+                      application/json:
+                        schema:
+                          type: object
+                          required: [name]
+                          properties:
+                            name: {type: string, minLength: 1, maxLength: 10}
+                            kind: {type: string, enum: [cat, dog]}
+                            tags:
+                              type: array
+                              maxItems: 2
+                              uniqueItems: true
+                              items: {type: string}
+                  responses: {"200": {description: ok}}
+        "#})
+        .unwrap()
+    }
+
+    fn derive(given: &Answers) -> Vec<Case> {
+        let api = api();
+        let item = OperationItem::find_by_id(&api, "updatePet")
+            .unwrap()
+            .unwrap();
+        cases(&api, &item, &mut Faker::new(Some(0)), given).unwrap()
+    }
+
+    #[test]
+    fn boundaries_and_violations() {
+        let cases = derive(&Answers::default());
+        let names = cases.iter().map(|x| x.name.as_str()).collect::<Vec<_>>();
+        for name in [
+            "valid",
+            "path.id:minimum",
+            "path.id:minimum-1",
+            "path.id:int32-max+1",
+            "query.limit:missing",
+            "query.limit:maximum+1",
+            "header.X-Trace:maxLength+1",
+            "body.name:missing",
+            "body.name:minLength-1",
+            "body.name:maxLength",
+            "body.name:maxLength+1",
+            "body.kind:not-enum",
+            "body.tags:maxItems+1",
+            "body.tags:duplicate",
+        ] {
+            assert!(names.contains(&name), "{name} not in {names:?}");
+        }
+        assert!(!names.contains(&"path.id:missing"));
+
+        let case = cases
+            .iter()
+            .find(|x| x.name == "body.name:maxLength+1")
+            .unwrap();
+        assert_eq!(case.expect, Expect::Invalid("maxLength"));
+        let name = &case.answers.body.as_ref().unwrap()["name"];
+        assert_eq!(name.as_str().unwrap().chars().count(), 11);
+    }
+
+    #[test]
+    fn bodies_break_only_their_constraint() {
+        let api = api();
+        let item = OperationItem::find_by_id(&api, "updatePet")
+            .unwrap()
+            .unwrap();
+        let schema = item
+            .operation
+            .request_body
+            .as_ref()
+            .unwrap()
+            .item(&api)
+            .unwrap()
+            .content["application/json"]
+            .schema
+            .as_ref()
+            .unwrap()
+            .item(&api)
+            .unwrap();
+        for case in derive(&Answers::default()) {
+            let body = case.answers.body.as_ref().unwrap();
+            let violations = validate::validate(&api, schema, body, "body").unwrap();
+            let field = case.name.split(':').next().unwrap();
+            if field.starts_with("body") {
+                assert!(case.expect.matches(&violations, field), "{}", case.name);
+            } else {
+                assert!(violations.is_empty(), "{}", case.name);
+            }
+        }
+    }
+
+    #[test]
+    fn given_parameters_are_kept() {
+        let given = Answers {
+            header: IndexMap::from([("X-Trace".to_owned(), json!("abc"))]),
+            ..Default::default()
+        };
+        for case in derive(&given) {
+            assert!(!case.name.starts_with("header."), "{}", case.name);
+            assert_eq!(case.answers.header["X-Trace"], json!("abc"));
+        }
+    }
+}
@@ -1,51 +1,242 @@
 use anyhow::{anyhow, Result};
 use http::Method;
+use indexmap::IndexMap;
 use indoc::indoc;
 use openapiv3::OpenAPI;
 use promptuity::{themes::FancyTheme, Term};
 use serde_json::{json, Value};
-use std::error::Error;
+use std::{
+    error::Error,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
 
 use clap::{
     builder::{styling, Styles},
-    Parser,
+    Parser, Subcommand,
 };
+use promptuity::{prompts::SelectOption, Promptuity};
 
 use crate::{
+    answers::{Answered, Answers},
+    cases,
+    collection::Collection,
+    config::{Config, CredentialHeader},
+    describe,
+    editor::EditorFormat,
     error::AppError,
-    fmt::{Formatter, RequestFormatter},
+    field::{self, Field},
+    fmt::{curl::CurlFormatter, Formatter, RequestFormatter},
+    history::{History, HistoryEntry},
+    interpolate::{self, Interpolator},
     prompt::Prompt,
+    req::RequestInit,
+};
+use oreq::{
+    fake::Faker,
+    prompts::{enumeration::Enumeration, PromptConfig},
+    schema::{
+        operation::OperationItem,
+        path::{base_paths, match_path, split_url},
+        read::ReadSchema,
+    },
+    validate,
 };
-use oreq::schema::read::ReadSchema;
 
 #[derive(Parser, Debug)]
 #[command(
     author,
     version,
     about,
-    arg_required_else_help = true,
     styles = styles(),
-    help_template = HELP_TEMPLATE
+    help_template = HELP_TEMPLATE,
+    args_conflicts_with_subcommands = true
 )]
 pub struct Cli {
-    #[arg(help = "OpenAPI schema path. Use a dash ('-') to read from standard input.", value_hint = clap::ValueHint::FilePath)]
-    pub schema: String,
-    #[arg(long, short, help = "Base URL", value_hint = clap::ValueHint::Url)]
+    #[command(subcommand)]
+    pub command: Option<Command>,
+    #[arg(help = "OpenAPI schema path. Use a dash ('-') to read from standard input. Defaults to `schema` in .oreq.toml", value_hint = clap::ValueHint::FilePath)]
+    pub schema: Option<String>,
+    #[arg(
+        help = "URL or path to request, e.g. https://api.github.com/repos/owner/repo?per_page=5. Matched against the paths of the schema",
+        value_hint = clap::ValueHint::Url,
+        conflicts_with = "path"
+    )]
+    pub url: Option<String>,
+    #[arg(long, short, help = "Base URL", value_hint = clap::ValueHint::Url, global = true)]
     pub base_url: Option<String>,
     #[arg(long, short, help = "Path to request")]
     pub path: Option<String>,
     #[arg(long = "request", short = 'X', help = "Method to use")]
     pub method: Option<Method>,
+    #[arg(
+        long,
+        help = "Operation to request by operationId, instead of the path and the method",
+        conflicts_with_all = ["path", "method", "url"]
+    )]
+    pub operation: Option<String>,
     #[arg(long = "param", short = 'P', help = "Path parameters", value_parser = parse_body)]
     pub path_param: Option<Vec<(String, serde_json::Value)>>,
     #[arg(long, short, help = "Query parameters", value_parser = parse_body)]
     pub query_param: Option<Vec<(String, serde_json::Value)>>,
     #[arg(long, short = 'H', value_parser = parse_key_val)]
     pub headers: Option<Vec<(String, serde_json::Value)>>,
-    #[arg(long, short, help = "Request body", value_parser = parse_body)]
-    pub field: Option<Vec<(String, serde_json::Value)>>,
-    #[arg(long = "format", help = "Output format", default_value = "curl")]
+    #[arg(
+        long,
+        short,
+        help = "Request body field. Nested paths (`a.b=1`, `tags[]=x`, `items[0].qty=2`) and raw JSON (`meta:=@meta.json`) are supported",
+        value_parser = Field::from_str
+    )]
+    pub field: Option<Vec<Field>>,
+    #[arg(
+        long = "format",
+        help = "Output format",
+        default_value = "curl",
+        global = true
+    )]
     pub fmt: Formatter,
+    #[arg(long, help = "Answers file (JSON or YAML) keyed by location and parameter name", value_hint = clap::ValueHint::FilePath)]
+    pub input: Option<PathBuf>,
+    #[arg(long, help = "Fail with the missing values instead of prompting")]
+    pub no_interactive: bool,
+    #[arg(
+        long,
+        help = "Edit the request body in $EDITOR instead of prompting its fields",
+        value_name = "FORMAT",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "yaml",
+        conflicts_with = "no_interactive"
+    )]
+    pub editor: Option<EditorFormat>,
+    #[arg(
+        long,
+        help = "Fill the parameters and the body which are not given with fake data instead of prompting them"
+    )]
+    pub fake: bool,
+    #[arg(
+        long,
+        short,
+        help = "Output the request without reviewing the answers",
+        global = true
+    )]
+    pub yes: bool,
+    #[arg(long, short, help = "Environment defined in .oreq.toml", global = true)]
+    pub env: Option<String>,
+    #[arg(
+        long,
+        help = "Keep ${VAR} and $(command) references as shell expressions in the output",
+        global = true
+    )]
+    pub keep_vars: bool,
+    #[arg(
+        long,
+        help = "Hide deprecated operations, parameters and properties",
+        global = true
+    )]
+    pub hide_deprecated: bool,
+    #[arg(
+        long,
+        help = "Prompt and accept readOnly properties of request bodies too",
+        global = true
+    )]
+    pub include_read_only: bool,
+    #[arg(
+        long,
+        help = "Prompt only the required parameters and properties",
+        global = true
+    )]
+    pub required_only: bool,
+    #[arg(
+        long,
+        help = "Seed of the fake data, to generate the same values again",
+        global = true
+    )]
+    pub seed: Option<u64>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    #[command(about = "List past requests")]
+    History {
+        #[arg(help = "Fuzzy search query")]
+        query: Option<String>,
+    },
+    #[command(about = "Output a past request again")]
+    Replay {
+        #[arg(help = "History ID. Select interactively if omitted")]
+        id: Option<usize>,
+        #[arg(long, help = "Send the request with curl")]
+        send: bool,
+    },
+    #[command(about = "Reopen the prompts with the answers of a past request as defaults")]
+    Edit {
+        #[arg(help = "History ID. Select interactively if omitted")]
+        id: Option<usize>,
+    },
+    #[command(about = "Save a past request to the collection next to the schema")]
+    Save {
+        #[arg(help = "Name of the request")]
+        name: String,
+        #[arg(long, help = "History ID. Defaults to the last request")]
+        id: Option<usize>,
+    },
+    #[command(about = "List the operations of the schema")]
+    List {
+        #[arg(help = "OpenAPI schema path. Defaults to `schema` in .oreq.toml", value_hint = clap::ValueHint::FilePath)]
+        schema: Option<String>,
+        #[arg(long, help = "Only list the operations with the tag")]
+        tag: Option<String>,
+        #[arg(long, help = "Print as JSON")]
+        json: bool,
+    },
+    #[command(
+        about = "Show the parameters, the body, the security and the responses of an operation"
+    )]
+    Describe {
+        #[arg(help = "OpenAPI schema path. Defaults to `schema` in .oreq.toml", value_hint = clap::ValueHint::FilePath)]
+        schema: Option<String>,
+        #[arg(help = "operationId")]
+        operation_id: Option<String>,
+    },
+    #[command(
+        about = "Generate fake parameters and a body of an operation, in the format of --input"
+    )]
+    Fake {
+        #[arg(help = "OpenAPI schema path. Defaults to `schema` in .oreq.toml", value_hint = clap::ValueHint::FilePath)]
+        schema: Option<String>,
+        #[arg(help = "operationId")]
+        operation_id: Option<String>,
+    },
+    #[command(about = "Generate boundary and negative cases of an operation from its constraints")]
+    Cases {
+        #[arg(help = "OpenAPI schema path. Defaults to `schema` in .oreq.toml", value_hint = clap::ValueHint::FilePath)]
+        schema: Option<String>,
+        #[arg(help = "operationId")]
+        operation_id: Option<String>,
+        #[arg(
+            long,
+            help = "Save the cases to the collection next to the schema instead of printing them"
+        )]
+        save: bool,
+    },
+    #[command(about = "Build a saved request without prompting")]
+    Run {
+        #[arg(help = "Name of the request")]
+        name: String,
+        #[arg(long, short, help = "OpenAPI schema path. Defaults to `schema` in .oreq.toml", value_hint = clap::ValueHint::FilePath)]
+        schema: Option<String>,
+    },
+}
+
+struct Session {
+    schema: String,
+    env: Option<String>,
+    api: OpenAPI,
+    /// Only required once a request is formatted, so a schema without servers can be browsed.
+    server: Option<String>,
+    headers: IndexMap<String, Value>,
+    credentials: Vec<CredentialHeader>,
 }
 
 fn parse_key_val(
@@ -55,10 +246,7 @@ fn parse_key_val(
     let key = key.trim();
     let value = value.trim();
 
-    let key = key.to_string();
-    let value = value.parse::<Value>().unwrap_or_else(|_| json!(value));
-
-    Ok((key, value))
+    Ok((key.to_string(), interpolate::flag_value(value)?))
 }
 
 fn parse_body(
@@ -68,10 +256,60 @@ fn parse_body(
     let key = key.trim();
     let value = value.trim();
 
-    let key = key.to_string();
-    let value = value.parse::<Value>().unwrap_or_else(|_| json!(value));
+    Ok((key.to_string(), interpolate::flag_value(value)?))
+}
+
+/// Collects `key=value` flags. A key given more than once becomes an array
+/// of the values, and its name is returned to be checked against the schema.
+fn collect_values(values: Vec<(String, Value)>) -> (IndexMap<String, Value>, Vec<String>) {
+    let mut map = IndexMap::<String, Value>::new();
+    let mut repeated = Vec::new();
+    for (key, value) in values {
+        match map.get_mut(&key) {
+            Some(Value::Array(items)) if repeated.contains(&key) => items.push(value),
+            Some(prev) => {
+                *prev = Value::Array(vec![prev.take(), value]);
+                repeated.push(key);
+            }
+            None => {
+                map.insert(key, value);
+            }
+        }
+    }
+
+    (map, repeated)
+}
+
+/// Interpolates the request and adds the credentials which are not given.
+fn interpolate(
+    interpolator: &mut Interpolator,
+    init: &RequestInit,
+    credentials: &[CredentialHeader],
+) -> Result<RequestInit, AppError> {
+    let mut init = interpolator.interpolate(init)?;
+    for credential in credentials {
+        if !init.header.iter().any(|(k, _)| k == &credential.name) {
+            let value = interpolator.reference(credential.reference.clone())?;
+            init.header.push((
+                credential.name.clone(),
+                json!(format!("{}{}", credential.prefix, value)),
+            ));
+        }
+    }
 
-    Ok((key, value))
+    Ok(init)
+}
+
+/// `oreq describe <operationId>` reads the schema from .oreq.toml
+fn schema_and_operation(
+    schema: &Option<String>,
+    operation_id: &Option<String>,
+) -> Result<(Option<String>, String), AppError> {
+    match (schema, operation_id) {
+        (Some(schema), Some(id)) => Ok((Some(schema.clone()), id.clone())),
+        (Some(id), None) => Ok((None, id.clone())),
+        _ => Err(anyhow!("operationId is required").into()),
+    }
 }
 
 fn styles() -> Styles {
@@ -96,50 +334,471 @@ const HELP_TEMPLATE: &str = indoc! {r#"
 
 impl Cli {
     pub fn run(&self) -> Result<(), AppError> {
-        let api = if self.schema == "-" {
+        match &self.command {
+            Some(Command::History { query }) => self.history(query.as_deref()),
+            Some(Command::Replay { id, send }) => self.replay(*id, *send),
+            Some(Command::Edit { id }) => self.edit(*id),
+            Some(Command::Save { name, id }) => self.save(name, *id),
+            Some(Command::Run { name, schema }) => self.run_saved(name, schema.clone()),
+            Some(Command::Cases {
+                schema,
+                operation_id,
+                save,
+            }) => {
+                let (schema, operation_id) = schema_and_operation(schema, operation_id)?;
+                self.cases(schema, &operation_id, *save)
+            }
+            Some(Command::List { schema, tag, json }) => {
+                let session = self.session(schema.clone(), self.env.clone())?;
+                println!(
+                    "{}",
+                    describe::list(&session.api, tag.as_deref(), *json, self.hide_deprecated)?
+                );
+                Ok(())
+            }
+            Some(Command::Describe {
+                schema,
+                operation_id,
+            }) => {
+                let (schema, operation_id) = schema_and_operation(schema, operation_id)?;
+                let session = self.session(schema, self.env.clone())?;
+                println!("{}", describe::describe(&session.api, &operation_id)?);
+                Ok(())
+            }
+            Some(Command::Fake {
+                schema,
+                operation_id,
+            }) => {
+                let (schema, operation_id) = schema_and_operation(schema, operation_id)?;
+                let session = self.session(schema, self.env.clone())?;
+                let item = OperationItem::find_by_id(&session.api, &operation_id)?
+                    .ok_or_else(|| anyhow!("Operation not found: {}", operation_id))?;
+                let mut answers = Answers::default();
+                answers.fake(&session.api, &item, &mut self.faker())?;
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&answers).map_err(|x| anyhow!(x))?
+                );
+                Ok(())
+            }
+            None => self.build(
+                self.schema.clone(),
+                self.env.clone(),
+                self.path.clone().or(self.url.clone()),
+                self.method.clone(),
+                Answers::default(),
+            ),
+        }
+    }
+
+    /// The faker of requests, whose strings are sent as they are generated.
+    fn faker(&self) -> Faker {
+        let mut faker = Faker::new(self.seed);
+        faker
+            .with_optional(!self.required_only)
+            .with_include_read_only(self.include_read_only)
+            .with_escape(Some(interpolate::escape));
+        faker
+    }
+
+    /// Loads the configuration, the schema and the values shared by every request.
+    fn session(
+        &self,
+        schema: Option<String>,
+        env_name: Option<String>,
+    ) -> Result<Session, AppError> {
+        let config = Config::discover()?.unwrap_or_default();
+        let env = config.environment(env_name.as_deref())?;
+
+        let schema = schema
+            .or(config
+                .schema_path()
+                .map(|x| x.to_string_lossy().to_string()))
+            .ok_or(AppError::NoSchema)?;
+        let api = if schema == "-" {
             ReadSchema::<OpenAPI>::get_schema_from_stdin()
         } else {
-            ReadSchema::<OpenAPI>::get_schema(self.schema.clone().into())
+            ReadSchema::<OpenAPI>::get_schema(schema.clone().into())
         }
         .map_err(|_| AppError::SchemaParseError)?;
-        let server = self
-            .base_url
-            .clone()
-            .or(api.schema.servers.first().map(|x| x.url.clone()))
-            .ok_or(AppError::NoServers)?;
+        let server = self.base_url.clone().or(env.base_url.clone()).or(api
+            .schema
+            .servers
+            .first()
+            .map(|x| interpolate::escape(&env.server_url(x))));
+
+        let mut headers = env
+            .headers()
+            .into_iter()
+            .map(|(k, v)| (k, json!(v)))
+            .collect::<IndexMap<_, _>>();
+        let (cli_headers, _) = collect_values(self.headers.clone().unwrap_or_default());
+        headers.extend(cli_headers);
+
+        let schema = if schema == "-" {
+            schema
+        } else {
+            std::fs::canonicalize(&schema)
+                .map(|x| x.to_string_lossy().to_string())
+                .unwrap_or(schema)
+        };
+
+        Ok(Session {
+            schema,
+            env: env_name,
+            api: api.schema,
+            server,
+            headers,
+            credentials: env.credentials(&config.dir),
+        })
+    }
+
+    fn build(
+        &self,
+        schema: Option<String>,
+        env_name: Option<String>,
+        path: Option<String>,
+        method: Option<Method>,
+        defaults: Answers,
+    ) -> Result<(), AppError> {
+        let session = self.session(schema, env_name)?;
+        let (path, method) = match &self.operation {
+            Some(id) => {
+                let item = OperationItem::find_by_id(&session.api, id)?
+                    .ok_or_else(|| anyhow!("Operation not found: {}", id))?;
+                let method = Method::from_bytes(item.method.as_bytes())
+                    .map_err(|_| anyhow!("Invalid method: {}", item.method))?;
+                (Some(item.path), Some(method))
+            }
+            None => (path, method),
+        };
+
+        let mut input = match &self.input {
+            Some(path) => Answers::load(path)?,
+            None => Answers::default(),
+        };
+        let (path_params, repeated_path) =
+            collect_values(self.path_param.clone().unwrap_or_default());
+        let (query_params, repeated_query) =
+            collect_values(self.query_param.clone().unwrap_or_default());
+        let (_, repeated_header) = collect_values(self.headers.clone().unwrap_or_default());
+        input.path.extend(path_params);
+        input.query.extend(query_params);
+        input.header.extend(session.headers.clone());
+        let repeated = [
+            ("path", repeated_path),
+            ("query", repeated_query),
+            ("header", repeated_header),
+        ]
+        .into_iter()
+        .flat_map(|(location, names)| names.into_iter().map(move |x| (location, x)))
+        .collect();
+        let path = path.map(|x| self.resolve_path(&session, &x, &mut input));
+        if let Some(fields) = &self.field {
+            let mut body = input.body.take().unwrap_or(Value::Null);
+            for field in fields {
+                field::assign(&mut body, field)?;
+            }
+            input.body = Some(body);
+        }
 
         let mut term = Term::default();
         let mut theme = FancyTheme::default();
-        let mut init = Prompt::new(api.schema, &mut term, &mut theme).run(
-            self.path.clone(),
-            self.method.clone(),
-            self.path_param
-                .clone()
-                .map(|x| x.into_iter().collect())
-                .unwrap_or_default(),
-            self.query_param
-                .clone()
-                .map(|x| x.into_iter().collect())
-                .unwrap_or_default(),
-            self.headers
-                .clone()
-                .map(|x| x.into_iter().collect())
-                .unwrap_or_default(),
-            self.field
-                .clone()
-                .map(|x| x.into_iter().collect())
-                .unwrap_or_default(),
-        )?;
-        init.base = server;
-        if let Some(headers) = self.headers.clone() {
-            init.header.extend(headers);
+        let answered = Prompt::new(session.api.clone(), &mut term, &mut theme)
+            .with_interactive(!self.no_interactive)
+            .with_repeated(repeated)
+            .with_review(!self.yes)
+            .with_editor(self.editor)
+            .with_fake(self.fake.then(|| self.faker()))
+            .with_config(PromptConfig {
+                hide_deprecated: self.hide_deprecated,
+                include_read_only: self.include_read_only,
+                required_only: self.required_only,
+                escape: Some(interpolate::escape),
+                ..Default::default()
+            })
+            .run(path, method, input, defaults)?;
+
+        self.output(&session, answered)
+    }
+
+    /// Matches a concrete URL or path to a path template of the schema and
+    /// pre-fills the parameters found in it. Values given by flags take precedence.
+    fn resolve_path(&self, session: &Session, path: &str, input: &mut Answers) -> String {
+        // Templates are taken as they are, since parsing would escape the braces.
+        if session.api.paths.paths.contains_key(path) {
+            return path.to_owned();
+        }
+
+        let (concrete, query) = split_url(path);
+        let servers = session
+            .api
+            .servers
+            .iter()
+            .map(|x| x.url.as_str())
+            .chain(session.server.as_deref());
+        let Some(matched) = match_path(&session.api, &concrete, &base_paths(servers)) else {
+            return path.to_owned();
+        };
+
+        let parse = |x: String| validate::parse_text(&x);
+        for (name, value) in matched.params {
+            input.path.entry(name).or_insert_with(|| parse(value));
+        }
+        let (query, _) = collect_values(query.into_iter().map(|(k, v)| (k, parse(v))).collect());
+        for (name, value) in query {
+            input.query.entry(name).or_insert(value);
+        }
+
+        matched.template
+    }
+
+    /// Formats the request, records it to the history and prints it.
+    fn output(&self, session: &Session, answered: Answered) -> Result<(), AppError> {
+        for warning in answered.deprecations(&session.api)? {
+            eprintln!("Warning: {}", warning);
+        }
+
+        let init = self.request(session, &answered)?;
+        let out = self.format(&init, &session.credentials)?;
+
+        if let Err(err) = History::open().and_then(|x| {
+            x.append(
+                session.schema.clone(),
+                session.env.clone(),
+                answered,
+                init.clone(),
+            )
+        }) {
+            eprintln!("Warning: failed to record history: {}", err);
         }
-        let fmt: Box<dyn RequestFormatter> = self.fmt.clone().into();
-        let out = fmt.format(&init)?;
 
         eprintln!();
         println!("{}", out);
 
         Ok(())
     }
+
+    /// The request with the server and the headers of the session.
+    fn request(&self, session: &Session, answered: &Answered) -> Result<RequestInit, AppError> {
+        let mut init = answered.request(&session.api)?;
+        init.base = session.server.clone().ok_or(AppError::NoServers)?;
+        for (k, v) in &session.headers {
+            if !init.header.iter().any(|(x, _)| x == k) {
+                init.header.push((k.clone(), v.clone()));
+            }
+        }
+
+        Ok(init)
+    }
+
+    /// Prints the cases of the operation as a sequence of requests, each after a
+    /// comment with its name and the expected outcome, or saves them to the collection.
+    fn cases(
+        &self,
+        schema: Option<String>,
+        operation_id: &str,
+        save: bool,
+    ) -> Result<(), AppError> {
+        let session = self.session(schema, self.env.clone())?;
+        let item = OperationItem::find_by_id(&session.api, operation_id)?
+            .ok_or_else(|| anyhow!("Operation not found: {}", operation_id))?;
+        // Headers of the environment, such as credentials, are neither varied nor saved.
+        let given = Answers {
+            header: session.headers.clone(),
+            ..Default::default()
+        };
+        // The values are escaped once the cases are derived from them.
+        let mut faker = self.faker();
+        faker.with_escape(None);
+        let cases = cases::cases(&session.api, &item, &mut faker, &given)?;
+
+        let mut collection = match save {
+            true if session.schema == "-" => {
+                return Err(anyhow!("Collections require a schema file").into())
+            }
+            true => Some(Collection::load(Path::new(&session.schema))?),
+            false => None,
+        };
+        let comment = match self.fmt {
+            Formatter::Curl => "#",
+            Formatter::Fetch => "//",
+        };
+        let count = cases.len();
+        for case in cases {
+            let mut answers = case.answers;
+            answers
+                .header
+                .retain(|k, _| !session.headers.contains_key(k));
+            answers.escape();
+            let answered = Answered {
+                method: item.method.clone(),
+                path: item.path.clone(),
+                operation_id: item.operation.operation_id.clone(),
+                answers,
+            };
+            let name = format!("{}/{}", operation_id, case.name);
+
+            match &mut collection {
+                Some(collection) => {
+                    collection.insert_expected(name, answered, Some(case.expect.to_string()))
+                }
+                None => {
+                    let out =
+                        self.format(&self.request(&session, &answered)?, &session.credentials)?;
+                    println!("{} {}: expect {}", comment, name, case.expect);
+                    match self.fmt {
+                        Formatter::Curl => println!("curl {}", out),
+                        Formatter::Fetch => println!("{}", out),
+                    }
+                }
+            }
+        }
+        if let Some(collection) = collection {
+            collection.save()?;
+            eprintln!("Saved {} cases to {}", count, collection.path().display());
+        }
+
+        Ok(())
+    }
+
+    fn save(&self, name: &str, id: Option<usize>) -> Result<(), AppError> {
+        let history = History::open()?;
+        let entry = match id {
+            Some(id) => history.get(id)?,
+            None => history
+                .entries()?
+                .pop()
+                .ok_or_else(|| anyhow!("No request in the history"))?,
+        };
+        if entry.schema == "-" {
+            return Err(anyhow!("The schema of #{} was read from standard input", entry.id).into());
+        }
+
+        let mut collection = Collection::load(Path::new(&entry.schema))?;
+        collection.insert(name.to_owned(), entry.answered);
+        collection.save()?;
+        eprintln!("Saved '{}' to {}", name, collection.path().display());
+
+        Ok(())
+    }
+
+    fn run_saved(&self, name: &str, schema: Option<String>) -> Result<(), AppError> {
+        let session = self.session(schema, self.env.clone())?;
+        if session.schema == "-" {
+            return Err(anyhow!("Collections require a schema file").into());
+        }
+
+        let collection = Collection::load(Path::new(&session.schema))?;
+        let saved = collection.get(name)?;
+        let answered = saved.resolve(name, &session.api, &session.headers)?;
+        if let Some(expect) = &saved.expect {
+            eprintln!("Expect: {}", expect);
+        }
+
+        self.output(&session, answered)
+    }
+
+    fn format(
+        &self,
+        init: &RequestInit,
+        credentials: &[CredentialHeader],
+    ) -> Result<String, AppError> {
+        let mut interpolator = Interpolator::new(self.keep_vars);
+        let init = interpolate(&mut interpolator, init, credentials)?;
+        let fmt: Box<dyn RequestFormatter> = self.fmt.clone().into();
+        let out = fmt.format(&init)?;
+        let out = interpolator.restore(out, |x| fmt.reference(x));
+
+        Ok(out)
+    }
+
+    fn history(&self, query: Option<&str>) -> Result<(), AppError> {
+        let history = History::open()?;
+        let entries = match query {
+            Some(query) => history.search(query)?,
+            None => history.entries()?,
+        };
+
+        for entry in entries {
+            println!(
+                "{:>4}  {}  {}  {}",
+                entry.id,
+                entry.timestamp.format("%Y-%m-%d %H:%M"),
+                entry.label(),
+                entry.schema
+            );
+        }
+
+        Ok(())
+    }
+
+    fn replay(&self, id: Option<usize>, send: bool) -> Result<(), AppError> {
+        let entry = self.select_history(id)?;
+        let config = Config::discover()?.unwrap_or_default();
+        let env = config.environment(self.env.as_deref().or(entry.env.as_deref()))?;
+        let credentials = env.credentials(&config.dir);
+
+        if send {
+            if !matches!(self.fmt, Formatter::Curl) {
+                return Err(anyhow!("--send requires the curl format").into());
+            }
+            let init = interpolate(&mut Interpolator::new(false), &entry.request, &credentials)?;
+            let status = std::process::Command::new("curl")
+                .args(CurlFormatter::args(&init)?)
+                .status()
+                .map_err(anyhow::Error::from)?;
+            if !status.success() {
+                return Err(anyhow!("curl exited with {}", status).into());
+            }
+        } else {
+            println!("{}", self.format(&entry.request, &credentials)?);
+        }
+
+        Ok(())
+    }
+
+    fn edit(&self, id: Option<usize>) -> Result<(), AppError> {
+        let entry = self.select_history(id)?;
+        if entry.schema == "-" {
+            return Err(anyhow!("The schema of #{} was read from standard input", entry.id).into());
+        }
+        let method = entry
+            .answered
+            .method
+            .parse::<Method>()
+            .map_err(anyhow::Error::from)?;
+
+        self.build(
+            Some(entry.schema),
+            self.env.clone().or(entry.env),
+            Some(entry.answered.path),
+            Some(method),
+            entry.answered.answers,
+        )
+    }
+
+    fn select_history(&self, id: Option<usize>) -> Result<HistoryEntry, AppError> {
+        let history = History::open()?;
+        if let Some(id) = id {
+            return Ok(history.get(id)?);
+        }
+
+        let entries = history.entries()?;
+        let options = entries
+            .iter()
+            .enumerate()
+            .rev()
+            .map(|(idx, x)| SelectOption::new(x.label(), idx).with_hint(x.hint()))
+            .collect();
+
+        let mut term = Term::default();
+        let mut theme = FancyTheme::default();
+        let mut provider = Promptuity::new(&mut term, &mut theme);
+        provider.with_intro("History").begin()?;
+        let idx = provider.prompt(&mut Enumeration::new("Request".to_owned(), options))?;
+        provider.finish()?;
+
+        Ok(entries[idx].clone())
+    }
 }
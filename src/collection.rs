@@ -0,0 +1,344 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use indexmap::IndexMap;
+use openapiv3::OpenAPI;
+use oreq::{
+    schema::{error::SchemaError, operation::OperationItem, reference::ReferenceOrExt},
+    validate,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    answers::{Answered, Answers},
+    interpolate,
+};
+
+pub const COLLECTION_VERSION: u32 = 1;
+
+#[derive(Debug, thiserror::Error)]
+pub enum CollectionError {
+    #[error("Unsupported collection version {0}. Please update oreq")]
+    UnsupportedVersion(u32),
+    #[error("Request '{0}' not found in {1}")]
+    NotFound(String, PathBuf),
+    #[error("Saved request '{0}' no longer matches the schema:\n{}", .1.iter().map(|x| format!("  - {}", x)).collect::<Vec<_>>().join("\n"))]
+    Mismatch(String, Vec<String>),
+    #[error("Failed to read collection: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid collection: {0}")]
+    Parse(#[from] serde_yaml::Error),
+    #[error(transparent)]
+    SchemaError(#[from] SchemaError),
+}
+
+/// Named requests saved next to the schema, meant to be committed with it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Collection {
+    pub version: u32,
+    #[serde(default)]
+    pub requests: IndexMap<String, SavedRequest>,
+    #[serde(skip)]
+    path: PathBuf,
+}
+
+/// A request identified by the operationId, so it survives path renames.
+/// The method and the path are used when the operation has no operationId.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedRequest {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub operation_id: Option<String>,
+    pub method: String,
+    pub path: String,
+    #[serde(default)]
+    pub answers: Answers,
+    /// The outcome expected by a case of `oreq cases`, e.g. `invalid (maxLength)`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expect: Option<String>,
+}
+
+impl Collection {
+    /// `api.yaml` is paired with `api.oreq.yaml`.
+    pub fn path_for(schema: &Path) -> PathBuf {
+        let stem = schema
+            .file_stem()
+            .map(|x| x.to_string_lossy().to_string())
+            .unwrap_or_default();
+        schema.with_file_name(format!("{}.oreq.yaml", stem))
+    }
+
+    pub fn load(schema: &Path) -> Result<Self, CollectionError> {
+        let path = Self::path_for(schema);
+        if !path.exists() {
+            return Ok(Self {
+                version: COLLECTION_VERSION,
+                requests: IndexMap::new(),
+                path,
+            });
+        }
+
+        let content = fs::read_to_string(&path)?;
+        let mut collection = serde_yaml::from_str::<Self>(&content)?;
+        if collection.version > COLLECTION_VERSION {
+            return Err(CollectionError::UnsupportedVersion(collection.version));
+        }
+        collection.path = path;
+
+        Ok(collection)
+    }
+
+    pub fn save(&self) -> Result<(), CollectionError> {
+        let content = serde_yaml::to_string(self)?;
+        fs::write(&self.path, content)?;
+
+        Ok(())
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn insert(&mut self, name: String, answered: Answered) {
+        self.insert_expected(name, answered, None);
+    }
+
+    pub fn insert_expected(&mut self, name: String, answered: Answered, expect: Option<String>) {
+        self.requests.insert(
+            name,
+            SavedRequest {
+                operation_id: answered.operation_id,
+                method: answered.method,
+                path: answered.path,
+                answers: answered.answers,
+                expect,
+            },
+        );
+    }
+
+    pub fn get(&self, name: &str) -> Result<&SavedRequest, CollectionError> {
+        self.requests
+            .get(name)
+            .ok_or_else(|| CollectionError::NotFound(name.to_owned(), self.path.clone()))
+    }
+}
+
+impl SavedRequest {
+    fn expects_invalid(&self) -> bool {
+        self.expect
+            .as_deref()
+            .is_some_and(|x| x.starts_with("invalid"))
+    }
+
+    /// Finds the operation in the schema and checks the answers still fit it.
+    /// `headers` are given outside of the collection, e.g. by `.oreq.toml`.
+    pub fn resolve(
+        &self,
+        name: &str,
+        api: &OpenAPI,
+        headers: &IndexMap<String, Value>,
+    ) -> Result<Answered, CollectionError> {
+        let item = match &self.operation_id {
+            Some(id) => OperationItem::find_by_id(api, id)?,
+            None => OperationItem::find(api, &self.method, &self.path)?,
+        };
+        let item = item.ok_or_else(|| {
+            let operation = self
+                .operation_id
+                .clone()
+                .unwrap_or(format!("{} {}", self.method, self.path));
+            CollectionError::Mismatch(
+                name.to_owned(),
+                vec![format!("operation '{}' not found", operation)],
+            )
+        })?;
+
+        let mut problems = Vec::new();
+        let parameters = item.parameters(api)?;
+        let answers = [
+            ("path", &self.answers.path),
+            ("query", &self.answers.query),
+            ("header", &self.answers.header),
+            ("cookie", &self.answers.cookie),
+        ];
+        for (location, values) in answers {
+            for key in values.keys() {
+                if !parameters
+                    .iter()
+                    .any(|(l, x)| *l == location && &x.name == key)
+                {
+                    problems.push(format!("unknown {} parameter '{}'", location, key));
+                }
+            }
+        }
+        for (location, parameter) in &parameters {
+            let values = answers.iter().find(|(l, _)| l == location).map(|x| x.1);
+            let given = values.is_some_and(|x| x.contains_key(&parameter.name))
+                || (*location == "header" && headers.contains_key(&parameter.name));
+            if parameter.required && !given {
+                problems.push(format!(
+                    "missing required {} parameter '{}'",
+                    location, parameter.name
+                ));
+            }
+        }
+
+        let request_body = item
+            .operation
+            .request_body
+            .as_ref()
+            .map(|x| x.item(api))
+            .transpose()?;
+        match (request_body, &self.answers.body) {
+            (None, Some(_)) => problems.push("the operation no longer takes a body".to_owned()),
+            (Some(body), None) if body.required => {
+                problems.push("missing required request body".to_owned())
+            }
+            // Cases expected to be rejected break the schema on purpose.
+            (Some(body), Some(value)) if !self.expects_invalid() => {
+                let schema = body
+                    .content
+                    .get("application/json")
+                    .and_then(|x| x.schema.as_ref());
+                if let Some(schema) = schema {
+                    let schema = schema.item(api)?;
+                    let literal = interpolate::literal(value);
+                    let mut violations = validate::validate(api, schema, &literal, "body")?;
+                    interpolate::skip_references(value, "body", &mut violations);
+                    problems.extend(violations.into_iter().map(|x| x.to_string()));
+                }
+            }
+            _ => {}
+        }
+
+        if !problems.is_empty() {
+            return Err(CollectionError::Mismatch(name.to_owned(), problems));
+        }
+
+        Ok(Answered {
+            method: item.method,
+            path: item.path,
+            operation_id: item.operation.operation_id,
+            answers: self.answers.clone(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn api() -> OpenAPI {
+        serde_yaml::from_str(indoc::indoc! {r#"
+            openapi: 3.0.0
+            info: {title: pets, version: "1"}
+            paths:
+              /pets/{id}:
+                put:
+                  operationId: updatePet
+                  parameters:
+                    - {name: id, in: path, required: true, schema: {type: integer}}
+                    - {name: X-Token, in: header, required: true, schema: {type: string}}
+                  requestBody:
+                    required: true
+                    content:
+                      application/json:
+                        schema:
+                          type: object
+                          required: [name]
+                          properties:
+                            name: {type: string, maxLength: 5}
+                  responses: {"200": {description: ok}}
+        "#})
+        .unwrap()
+    }
+
+    fn saved(answers: Value, expect: Option<&str>) -> SavedRequest {
+        SavedRequest {
+            operation_id: Some("updatePet".to_owned()),
+            method: "PUT".to_owned(),
+            path: "/pets/{id}".to_owned(),
+            answers: serde_json::from_value(answers).unwrap(),
+            expect: expect.map(|x| x.to_owned()),
+        }
+    }
+
+    fn problems(saved: &SavedRequest, headers: &IndexMap<String, Value>) -> Vec<String> {
+        match saved.resolve("pet", &api(), headers) {
+            Err(CollectionError::Mismatch(name, problems)) => {
+                assert_eq!(name, "pet");
+                problems
+            }
+            result => panic!("not a mismatch: {:?}", result.map(|x| x.answers)),
+        }
+    }
+
+    #[test]
+    fn resolve_valid() {
+        let headers = IndexMap::from([("X-Token".to_owned(), json!("${TOKEN}"))]);
+        let saved = saved(json!({"path": {"id": 1}, "body": {"name": "Tama"}}), None);
+        let answered = saved.resolve("pet", &api(), &headers).unwrap();
+        assert_eq!(answered.path, "/pets/{id}");
+        assert_eq!(answered.method, "PUT");
+    }
+
+    #[test]
+    fn resolve_mismatches() {
+        let saved = saved(
+            json!({"query": {"limit": 1}, "body": {"name": "Tama"}}),
+            None,
+        );
+        assert_eq!(
+            problems(&saved, &IndexMap::new()),
+            [
+                "unknown query parameter 'limit'",
+                "missing required path parameter 'id'",
+                "missing required header parameter 'X-Token'",
+            ]
+        );
+
+        let mut saved = saved;
+        saved.operation_id = Some("deletePet".to_owned());
+        assert_eq!(
+            problems(&saved, &IndexMap::new()),
+            ["operation 'deletePet' not found"]
+        );
+    }
+
+    #[test]
+    fn resolve_body_violations() {
+        let answers = json!({
+            "path": {"id": 1},
+            "header": {"X-Token": "a"},
+            "body": {"name": "Tama Chan"}
+        });
+        assert_eq!(
+            problems(&saved(answers.clone(), None), &IndexMap::new()),
+            ["body.name: Value is too long. Maximum length is 5 (maxLength)"]
+        );
+        assert_eq!(
+            problems(
+                &saved(json!({"path": {"id": 1}, "header": {"X-Token": "a"}}), None),
+                &IndexMap::new()
+            ),
+            ["missing required request body"]
+        );
+
+        // A case expected to be rejected is sent as it is saved.
+        let case = saved(answers, Some("invalid (maxLength)"));
+        assert!(case.resolve("pet", &api(), &IndexMap::new()).is_ok());
+
+        // References are only known once the request is interpolated.
+        let answers = json!({
+            "path": {"id": 1},
+            "header": {"X-Token": "a"},
+            "body": {"name": "${PET_NAME}"}
+        });
+        let saved = saved(answers, None);
+        assert!(saved.resolve("pet", &api(), &IndexMap::new()).is_ok());
+    }
+}
@@ -0,0 +1,268 @@
+use std::{
+    env::current_dir,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, Context as _};
+use indexmap::IndexMap;
+use openapiv3::Server;
+use serde::Deserialize;
+
+use crate::interpolate::Reference;
+
+pub const CONFIG_FILE_NAME: &str = ".oreq.toml";
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Config {
+    pub schema: Option<PathBuf>,
+    pub default_env: Option<String>,
+    #[serde(flatten)]
+    pub defaults: Environment,
+    #[serde(default, alias = "profile")]
+    pub env: IndexMap<String, Environment>,
+    #[serde(skip)]
+    pub dir: PathBuf,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Environment {
+    pub base_url: Option<String>,
+    #[serde(default)]
+    pub variables: IndexMap<String, String>,
+    #[serde(default)]
+    pub headers: IndexMap<String, String>,
+    #[serde(default)]
+    pub credentials: Vec<Credential>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Credential {
+    pub header: String,
+    pub prefix: Option<String>,
+    #[serde(flatten)]
+    pub source: CredentialSource,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CredentialSource {
+    Env(String),
+    File(PathBuf),
+    Command(String),
+}
+
+/// A credential added to the request when it is formatted, so the secret and
+/// where it comes from are never written to the answers or the history.
+#[derive(Debug, Clone)]
+pub struct CredentialHeader {
+    pub name: String,
+    pub prefix: String,
+    pub reference: Reference,
+}
+
+impl Config {
+    /// Searches `.oreq.toml` from the current directory up to the root.
+    pub fn discover() -> anyhow::Result<Option<Self>> {
+        Self::discover_from(&current_dir()?)
+    }
+
+    /// Searches `.oreq.toml` from `start` up to the root.
+    fn discover_from(start: &Path) -> anyhow::Result<Option<Self>> {
+        for dir in start.ancestors() {
+            let path = dir.join(CONFIG_FILE_NAME);
+            if path.is_file() {
+                return Self::load(&path).map(Some);
+            }
+        }
+
+        Ok(None)
+    }
+
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let content = fs::read_to_string(path)?;
+        let mut config = toml::from_str::<Config>(&content)
+            .with_context(|| format!("Failed to parse {}", path.display()))?;
+        config.dir = path
+            .parent()
+            .map(|x| x.to_owned())
+            .unwrap_or(current_dir()?);
+
+        Ok(config)
+    }
+
+    pub fn schema_path(&self) -> Option<PathBuf> {
+        self.schema.as_ref().map(|x| self.dir.join(x))
+    }
+
+    /// Merges the top-level defaults with the selected environment.
+    /// Values of the environment take precedence.
+    pub fn environment(&self, name: Option<&str>) -> anyhow::Result<Environment> {
+        let name = name.or(self.default_env.as_deref());
+        let mut env = self.defaults.clone();
+
+        if let Some(name) = name {
+            let selected = self.env.get(name).ok_or_else(|| {
+                anyhow!("Environment '{}' not found in {}", name, CONFIG_FILE_NAME)
+            })?;
+
+            if let Some(base_url) = &selected.base_url {
+                env.base_url = Some(base_url.clone());
+            }
+            env.variables.extend(selected.variables.clone());
+            env.headers.extend(selected.headers.clone());
+            env.credentials.extend(selected.credentials.clone());
+        }
+
+        Ok(env)
+    }
+}
+
+impl Environment {
+    /// Resolves the URL of the server, substituting the server variables.
+    pub fn server_url(&self, server: &Server) -> String {
+        let variables = server.variables.clone().unwrap_or_default();
+
+        variables
+            .iter()
+            .fold(server.url.clone(), |acc, (name, variable)| {
+                let value = self.variables.get(name).unwrap_or(&variable.default);
+                acc.replace(&format!("{{{}}}", name), value)
+            })
+    }
+
+    pub fn headers(&self) -> Vec<(String, String)> {
+        self.headers
+            .iter()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect()
+    }
+
+    /// The credential headers, with the files relative to `dir`.
+    pub fn credentials(&self, dir: &Path) -> Vec<CredentialHeader> {
+        self.credentials
+            .iter()
+            .map(|credential| CredentialHeader {
+                name: credential.header.clone(),
+                prefix: credential.prefix.clone().unwrap_or_default(),
+                reference: match &credential.source {
+                    CredentialSource::Env(name) => Reference::Env(name.clone()),
+                    CredentialSource::File(path) => Reference::File(dir.join(path)),
+                    CredentialSource::Command(command) => Reference::Command(command.clone()),
+                },
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("oreq-config-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn discover_nearest_ancestor() {
+        let root = temp_dir("discover");
+        let project = root.join("project");
+        let nested = project.join("src/deep");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(root.join(CONFIG_FILE_NAME), "schema = \"outer.yaml\"").unwrap();
+        fs::write(project.join(CONFIG_FILE_NAME), "schema = \"api.yaml\"").unwrap();
+
+        let config = Config::discover_from(&nested).unwrap().unwrap();
+        assert_eq!(config.dir, project);
+        assert_eq!(config.schema_path(), Some(project.join("api.yaml")));
+
+        let config = Config::discover_from(&root).unwrap().unwrap();
+        assert_eq!(config.schema_path(), Some(root.join("outer.yaml")));
+
+        fs::write(project.join(CONFIG_FILE_NAME), "schema = [").unwrap();
+        assert!(Config::discover_from(&nested).is_err());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn merge_environment_with_defaults() {
+        let config = toml::from_str::<Config>(indoc::indoc! {r#"
+            base_url = "http://localhost:8080"
+            default_env = "dev"
+
+            [variables]
+            version = "v1"
+            region = "us"
+
+            [headers]
+            X-Client = "oreq"
+
+            [[credentials]]
+            header = "X-Api-Key"
+            env = "API_KEY"
+
+            [env.dev.variables]
+            region = "eu"
+
+            [env.prod]
+            base_url = "https://api.example.com"
+
+            [env.prod.headers]
+            X-Client = "oreq-prod"
+
+            [[env.prod.credentials]]
+            header = "Authorization"
+            prefix = "Bearer "
+            file = "token.txt"
+        "#})
+        .unwrap();
+
+        let dev = config.environment(None).unwrap();
+        assert_eq!(dev.base_url.as_deref(), Some("http://localhost:8080"));
+        assert_eq!(dev.variables["version"], "v1");
+        assert_eq!(dev.variables["region"], "eu");
+
+        let prod = config.environment(Some("prod")).unwrap();
+        assert_eq!(prod.base_url.as_deref(), Some("https://api.example.com"));
+        assert_eq!(prod.variables["region"], "us");
+        assert_eq!(
+            prod.headers(),
+            [("X-Client".to_owned(), "oreq-prod".to_owned())]
+        );
+        let credentials = prod.credentials(Path::new("/project"));
+        assert_eq!(credentials.len(), 2);
+        assert_eq!(
+            credentials[0].reference,
+            Reference::Env("API_KEY".to_owned())
+        );
+        assert_eq!(credentials[1].prefix, "Bearer ");
+        assert_eq!(
+            credentials[1].reference,
+            Reference::File(PathBuf::from("/project/token.txt"))
+        );
+
+        assert!(config.environment(Some("staging")).is_err());
+    }
+
+    #[test]
+    fn server_url_variables() {
+        let server = serde_json::from_value::<Server>(serde_json::json!({
+            "url": "https://{region}.example.com/{version}",
+            "variables": {
+                "region": {"default": "us"},
+                "version": {"default": "v1"}
+            }
+        }))
+        .unwrap();
+        let env = Environment {
+            variables: [("region".to_owned(), "eu".to_owned())]
+                .into_iter()
+                .collect(),
+            ..Default::default()
+        };
+        assert_eq!(env.server_url(&server), "https://eu.example.com/v1");
+    }
+}
@@ -0,0 +1,268 @@
+use std::fmt::Write as _;
+
+use anyhow::anyhow;
+use openapiv3::{
+    APIKeyLocation, OpenAPI, ParameterSchemaOrContent, SecurityRequirement, SecurityScheme,
+};
+use oreq::schema::{describe::SchemaNode, operation::OperationItem, reference::ReferenceOrExt};
+use serde::Serialize;
+
+use crate::error::AppError;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct OperationSummary {
+    method: String,
+    path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    operation_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    summary: Option<String>,
+    tags: Vec<String>,
+    deprecated: bool,
+}
+
+/// Prints the operations grouped by tag, or as a JSON array.
+pub fn list(
+    api: &OpenAPI,
+    tag: Option<&str>,
+    json: bool,
+    hide_deprecated: bool,
+) -> Result<String, AppError> {
+    let mut items = OperationItem::all(api)?
+        .into_iter()
+        .filter(|x| tag.is_none_or(|tag| x.operation.tags.iter().any(|x| x == tag)))
+        .filter(|x| !(hide_deprecated && x.operation.deprecated))
+        .collect::<Vec<_>>();
+    OperationItem::group_by_tag(api, &mut items);
+
+    if json {
+        let summaries = items
+            .into_iter()
+            .map(|x| OperationSummary {
+                method: x.method,
+                path: x.path,
+                operation_id: x.operation.operation_id,
+                summary: x.operation.summary,
+                tags: x.operation.tags,
+                deprecated: x.operation.deprecated,
+            })
+            .collect::<Vec<_>>();
+        return Ok(serde_json::to_string_pretty(&summaries).map_err(anyhow::Error::from)?);
+    }
+
+    let path_width = items.iter().map(|x| x.path.len()).max().unwrap_or_default();
+    let id_width = items
+        .iter()
+        .map(|x| x.operation.operation_id.as_ref().map_or(0, |x| x.len()))
+        .max()
+        .unwrap_or_default();
+
+    let mut out = String::new();
+    let mut group = None;
+    for item in items {
+        let tag = item.operation.tags.first().cloned();
+        if group.as_ref() != Some(&tag) {
+            if group.is_some() {
+                writeln!(out).unwrap();
+            }
+            writeln!(out, "{}", tag.clone().unwrap_or("(untagged)".to_owned())).unwrap();
+            group = Some(tag);
+        }
+        let line = format!(
+            "  {:<7} {:<path_width$}  {:<id_width$}  {}",
+            item.method,
+            item.path,
+            item.operation.operation_id.unwrap_or_default(),
+            item.operation.summary.unwrap_or_default()
+        );
+        let line = if item.operation.deprecated {
+            format!("{}  (deprecated)", line.trim_end())
+        } else {
+            line
+        };
+        writeln!(out, "{}", line.trim_end()).unwrap();
+    }
+
+    Ok(out.trim_end().to_owned())
+}
+
+/// Prints the parameters, the request body, the security and the responses of the operation.
+pub fn describe(api: &OpenAPI, operation_id: &str) -> Result<String, AppError> {
+    let item = OperationItem::find_by_id(api, operation_id)?
+        .ok_or_else(|| anyhow!("Operation not found: {}", operation_id))?;
+    let operation = &item.operation;
+
+    let mut out = String::new();
+    writeln!(out, "{} {}  {}", item.method, item.path, operation_id).unwrap();
+    if operation.deprecated {
+        writeln!(out, "Deprecated").unwrap();
+    }
+    for text in [&operation.summary, &operation.description]
+        .into_iter()
+        .flatten()
+    {
+        writeln!(out, "{}", text).unwrap();
+    }
+    if !operation.tags.is_empty() {
+        writeln!(out, "Tags: {}", operation.tags.join(", ")).unwrap();
+    }
+
+    let parameters = item.parameters(api)?;
+    if !parameters.is_empty() {
+        writeln!(out, "\nParameters").unwrap();
+        for (location, parameter) in parameters {
+            let node = match &parameter.format {
+                ParameterSchemaOrContent::Schema(schema) => {
+                    Some(SchemaNode::from_ref(api, parameter.name.clone(), schema)?)
+                }
+                ParameterSchemaOrContent::Content(_) => None,
+            };
+            let mut line = format!(
+                "  {:<6} {}{}",
+                location,
+                parameter.name,
+                if parameter.required { "*" } else { "" }
+            );
+            if let Some(node) = &node {
+                write!(line, ": {}", node.type_name).unwrap();
+                if !node.constraints.is_empty() {
+                    write!(line, "  ({})", node.constraints.join(", ")).unwrap();
+                }
+            }
+            if parameter.deprecated == Some(true) {
+                line.push_str("  deprecated");
+            }
+            if let Some(description) = &parameter.description {
+                write!(line, "  — {}", first_line(description)).unwrap();
+            }
+            writeln!(out, "{}", line).unwrap();
+        }
+    }
+
+    if let Some(body) = &operation.request_body {
+        let body = body.item(api)?;
+        for (media_type, content) in &body.content {
+            writeln!(
+                out,
+                "\nRequest Body ({}{})",
+                media_type,
+                if body.required { ", required" } else { "" }
+            )
+            .unwrap();
+            if let Some(schema) = &content.schema {
+                let node = SchemaNode::from_ref(api, String::new(), schema)?;
+                write_tree(&mut out, &node, "  ", None);
+            }
+        }
+    }
+
+    let security = operation.security.as_ref().or(api.security.as_ref());
+    if let Some(security) = security {
+        writeln!(out, "\nSecurity").unwrap();
+        if security.is_empty() {
+            writeln!(out, "  none").unwrap();
+        }
+        for requirement in security {
+            writeln!(out, "  {}", security_requirement(api, requirement)).unwrap();
+        }
+    }
+
+    writeln!(out, "\nResponses").unwrap();
+    let responses = operation
+        .responses
+        .responses
+        .iter()
+        .map(|(code, response)| (code.to_string(), response))
+        .chain(
+            operation
+                .responses
+                .default
+                .as_ref()
+                .map(|x| ("default".to_owned(), x)),
+        );
+    for (code, response) in responses {
+        let response = response.item(api)?;
+        let media_types = response.content.keys().cloned().collect::<Vec<_>>();
+        let mut line = format!("  {:<7} {}", code, first_line(&response.description));
+        if !media_types.is_empty() {
+            write!(line, "  [{}]", media_types.join(", ")).unwrap();
+        }
+        writeln!(out, "{}", line).unwrap();
+    }
+
+    Ok(out.trim_end().to_owned())
+}
+
+fn security_requirement(api: &OpenAPI, requirement: &SecurityRequirement) -> String {
+    if requirement.is_empty() {
+        return "none".to_owned();
+    }
+
+    requirement
+        .iter()
+        .map(|(name, scopes)| {
+            let scheme = api
+                .components
+                .as_ref()
+                .and_then(|x| x.security_schemes.get(name))
+                .and_then(|x| x.as_item());
+            let kind = match scheme {
+                Some(SecurityScheme::APIKey { location, name, .. }) => {
+                    let location = match location {
+                        APIKeyLocation::Query => "query",
+                        APIKeyLocation::Header => "header",
+                        APIKeyLocation::Cookie => "cookie",
+                    };
+                    format!("apiKey in {} '{}'", location, name)
+                }
+                Some(SecurityScheme::HTTP { scheme, .. }) => format!("http {}", scheme),
+                Some(SecurityScheme::OAuth2 { .. }) => "oauth2".to_owned(),
+                Some(SecurityScheme::OpenIDConnect { .. }) => "openIdConnect".to_owned(),
+                None => "unknown".to_owned(),
+            };
+            if scopes.is_empty() {
+                format!("{} ({})", name, kind)
+            } else {
+                format!("{} ({}) scopes: {}", name, kind, scopes.join(", "))
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" + ")
+}
+
+/// Writes the node and its children with box drawing characters.
+/// `branch` is None for the root, which is printed without a name.
+fn write_tree(out: &mut String, node: &SchemaNode, indent: &str, branch: Option<bool>) {
+    let mut line = match branch {
+        Some(last) => format!(
+            "{}{}{}{}: {}",
+            indent,
+            if last { "└─ " } else { "├─ " },
+            node.name,
+            if node.required { "*" } else { "" },
+            node.type_name
+        ),
+        None => format!("{}{}", indent, node.type_name),
+    };
+    if !node.constraints.is_empty() {
+        write!(line, "  ({})", node.constraints.join(", ")).unwrap();
+    }
+    if let Some(description) = &node.description {
+        write!(line, "  — {}", first_line(description)).unwrap();
+    }
+    writeln!(out, "{}", line).unwrap();
+
+    let indent = match branch {
+        Some(true) => format!("{}   ", indent),
+        Some(false) => format!("{}│  ", indent),
+        None => indent.to_owned(),
+    };
+    for (idx, child) in node.children.iter().enumerate() {
+        write_tree(out, child, &indent, Some(idx + 1 == node.children.len()));
+    }
+}
+
+fn first_line(text: &str) -> &str {
+    text.lines().next().unwrap_or_default()
+}
@@ -0,0 +1,453 @@
+use std::{
+    env, fmt, fs,
+    io::Write,
+    path::PathBuf,
+    process::{Command, ExitStatus, Stdio},
+};
+
+use clap::ValueEnum;
+use openapiv3::{OpenAPI, Schema, SchemaKind, Type};
+use serde::{
+    de::{DeserializeSeed, MapAccess, SeqAccess, Visitor},
+    Deserialize, Deserializer,
+};
+use serde_json::{Map, Value};
+
+use crate::interpolate;
+use oreq::{
+    schema::{
+        describe::{constraints, type_name},
+        error::SchemaError,
+        reference::ReferenceOrExt,
+        skeleton::skeleton,
+    },
+    validate,
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum EditorError {
+    #[error("Failed to open the editor '{0}': {1}")]
+    Spawn(String, std::io::Error),
+    #[error("The editor '{0}' exited with {1}")]
+    Exit(String, ExitStatus),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    SchemaError(#[from] SchemaError),
+}
+
+#[derive(Debug, Clone, Copy, Default, ValueEnum)]
+pub(crate) enum EditorFormat {
+    #[default]
+    #[value(help = "YAML with the types and the constraints as comments")]
+    Yaml,
+    #[value(help = "JSON")]
+    Json,
+}
+
+impl EditorFormat {
+    fn extension(&self) -> &'static str {
+        match self {
+            EditorFormat::Yaml => "yaml",
+            EditorFormat::Json => "json",
+        }
+    }
+
+    /// Starts the lines written by oreq, which are removed before parsing.
+    fn marker(&self) -> &'static str {
+        match self {
+            EditorFormat::Yaml => "#!",
+            EditorFormat::Json => "//!",
+        }
+    }
+}
+
+/// Edits a request body in `$VISUAL` or `$EDITOR`, until it is valid against the schema.
+pub struct Editor<'a> {
+    api: &'a OpenAPI,
+    schema: &'a Schema,
+    format: EditorFormat,
+    include_read_only: bool,
+}
+
+impl<'a> Editor<'a> {
+    pub fn new(api: &'a OpenAPI, schema: &'a Schema, format: EditorFormat) -> Self {
+        Self {
+            api,
+            schema,
+            format,
+            include_read_only: false,
+        }
+    }
+
+    /// List the optional readOnly properties in the skeleton too.
+    pub fn with_include_read_only(&mut self, include_read_only: bool) -> &mut Self {
+        self.include_read_only = include_read_only;
+        self
+    }
+
+    /// Opens a skeleton of the schema completing `seed`. The file is opened again
+    /// with the errors on top until it is valid. An emptied file returns `None`.
+    pub fn edit(&self, seed: Option<&Value>) -> Result<Option<Value>, EditorError> {
+        let value = skeleton(self.api, self.schema, seed, Some(interpolate::escape))?;
+        let mut text = match self.format {
+            EditorFormat::Yaml => self.yaml(self.schema, &value, 0)?.join("\n"),
+            EditorFormat::Json => serde_json::to_string_pretty(&value).unwrap_or_default(),
+        };
+        let temp = TempFile::create(self.format.extension())?;
+        let path = &temp.0;
+
+        let mut errors = Vec::new();
+        let result = loop {
+            let content = format!("{}{}\n", self.header(&errors), text);
+            // The file is opened without creating it, so it is the one created above.
+            fs::OpenOptions::new()
+                .write(true)
+                .truncate(true)
+                .open(path)
+                .and_then(|mut file| file.write_all(content.as_bytes()))?;
+            open(path)?;
+
+            let marker = self.format.marker();
+            text = fs::read_to_string(path)?
+                .lines()
+                .skip_while(|x| x.starts_with(marker))
+                .collect::<Vec<_>>()
+                .join("\n");
+            if text.trim().is_empty() {
+                break None;
+            }
+
+            errors = match self.parse(&text) {
+                Ok(value) => {
+                    // Only comments are left when no property is set.
+                    let value = match (value, &self.schema.schema_kind) {
+                        (Value::Null, SchemaKind::Type(Type::Object(_))) => {
+                            Value::Object(Map::new())
+                        }
+                        (value, _) => value,
+                    };
+                    let violations = validate::validate(
+                        self.api,
+                        self.schema,
+                        &interpolate::literal(&value),
+                        "body",
+                    )?;
+                    if violations.is_empty() {
+                        break Some(value);
+                    }
+                    violations.into_iter().map(|x| x.to_string()).collect()
+                }
+                Err(err) => vec![err],
+            };
+        };
+
+        Ok(result)
+    }
+
+    fn parse(&self, text: &str) -> Result<Value, String> {
+        match self.format {
+            // Plain scalars are text, so they are typed after the schema as flags are.
+            EditorFormat::Yaml => {
+                let invalid = |x: serde_yaml::Error| format!("Invalid YAML: {}", x);
+                let value = serde_yaml::from_str::<Value>(text).map_err(invalid)?;
+                let value = Verbatim(&value)
+                    .deserialize(serde_yaml::Deserializer::from_str(text))
+                    .map_err(invalid)?;
+                validate::coerce(self.api, self.schema, value).map_err(|x| x.to_string())
+            }
+            // JSON has no comments, so only whole `//` lines are allowed.
+            EditorFormat::Json => {
+                let text = text
+                    .lines()
+                    .filter(|x| !x.trim_start().starts_with("//"))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                serde_json::from_str(&text).map_err(|x| format!("Invalid JSON: {}", x))
+            }
+        }
+    }
+
+    fn header(&self, errors: &[String]) -> String {
+        let marker = self.format.marker();
+        let mut lines = vec![format!(
+            "{} Request Body. Save and quit to continue, or empty the file to go back to the prompts.",
+            marker
+        )];
+        lines.extend(errors.iter().map(|x| format!("{} error: {}", marker, x)));
+
+        lines
+            .into_iter()
+            .map(|x| format!("{}\n", x))
+            .collect::<String>()
+    }
+
+    /// The value as YAML lines, with the type and the constraints of each property
+    /// above it. Optional properties which are not set are listed commented out.
+    fn yaml(
+        &self,
+        schema: &Schema,
+        value: &Value,
+        indent: usize,
+    ) -> Result<Vec<String>, EditorError> {
+        let pad = " ".repeat(indent);
+        let mut lines = Vec::new();
+        match (&schema.schema_kind, value) {
+            (SchemaKind::Type(Type::Object(object)), Value::Object(map))
+                if !map.is_empty() || !object.properties.is_empty() =>
+            {
+                for (key, property) in &object.properties {
+                    let property = property.clone().unbox();
+                    let property = property.item(self.api)?;
+                    let required = object.required.contains(key);
+                    let value = map.get(key);
+                    if value.is_none() && property.schema_data.read_only && !self.include_read_only
+                    {
+                        continue;
+                    }
+
+                    if let Some(description) = &property.schema_data.description {
+                        for line in description.lines() {
+                            lines.push(format!("{}# {}", pad, line));
+                        }
+                    }
+                    lines.push(format!("{}# {}", pad, annotation(property, required)));
+                    match value {
+                        Some(value) => lines.extend(self.entry(key, property, value, indent)?),
+                        None => {
+                            let value =
+                                skeleton(self.api, property, None, Some(interpolate::escape))?;
+                            lines.push(format!("{}# {}: {}", pad, key, flow(&value)));
+                        }
+                    }
+                }
+                for (key, value) in map {
+                    if !object.properties.contains_key(key) {
+                        lines.push(format!("{}{}: {}", pad, key, flow(value)));
+                    }
+                }
+            }
+            (SchemaKind::Type(Type::Array(array)), Value::Array(items)) if !items.is_empty() => {
+                let item_schema = match &array.items {
+                    Some(items) => Some(items.clone().unbox().item(self.api)?.clone()),
+                    None => None,
+                };
+                for item in items {
+                    let item_lines = match &item_schema {
+                        Some(schema) => self.yaml(schema, item, indent + 2)?,
+                        None => vec![format!("{}  {}", pad, flow(item))],
+                    };
+                    // The first line of the item follows the dash, unless it is a comment.
+                    match item_lines.split_first() {
+                        Some((first, rest)) if !first.trim_start().starts_with('#') => {
+                            lines.push(format!("{}- {}", pad, first.trim_start()));
+                            lines.extend(rest.iter().cloned());
+                        }
+                        _ => {
+                            lines.push(format!("{}-", pad));
+                            lines.extend(item_lines);
+                        }
+                    }
+                }
+            }
+            (_, value) => lines.push(format!("{}{}", pad, flow(value))),
+        }
+
+        Ok(lines)
+    }
+
+    fn entry(
+        &self,
+        key: &str,
+        schema: &Schema,
+        value: &Value,
+        indent: usize,
+    ) -> Result<Vec<String>, EditorError> {
+        let pad = " ".repeat(indent);
+        let nested = match value {
+            Value::Object(x) => !x.is_empty(),
+            Value::Array(x) => !x.is_empty(),
+            _ => false,
+        };
+        if !nested {
+            return Ok(vec![format!("{}{}: {}", pad, key, flow(value))]);
+        }
+
+        let mut lines = vec![format!("{}{}:", pad, key)];
+        lines.extend(self.yaml(schema, value, indent + 2)?);
+        Ok(lines)
+    }
+}
+
+/// Reads a YAML document again along its parsed value, keeping the numbers
+/// which parsing would change as they are written, e.g. `1.10`.
+struct Verbatim<'a>(&'a Value);
+
+impl<'de> DeserializeSeed<'de> for Verbatim<'_> {
+    type Value = Value;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Value, D::Error> {
+        match self.0 {
+            // Numbers which are not JSON, e.g. `0x1A`, are kept as parsed.
+            Value::Number(_) => {
+                let text = String::deserialize(deserializer)?;
+                Ok(match text.parse::<serde_json::Number>() {
+                    Ok(_) => validate::parse_text(&text),
+                    Err(_) => self.0.clone(),
+                })
+            }
+            Value::Array(_) => deserializer.deserialize_seq(self),
+            Value::Object(_) => deserializer.deserialize_map(self),
+            _ => Value::deserialize(deserializer),
+        }
+    }
+}
+
+impl<'de> Visitor<'de> for Verbatim<'_> {
+    type Value = Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Value, A::Error> {
+        let mut items = Vec::new();
+        while let Some(item) =
+            seq.next_element_seed(Verbatim(self.0.get(items.len()).unwrap_or(&Value::Null)))?
+        {
+            items.push(item);
+        }
+        Ok(Value::Array(items))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Value, A::Error> {
+        let mut entries = Map::new();
+        while let Some(key) = map.next_key::<String>()? {
+            let value = map.next_value_seed(Verbatim(self.0.get(&key).unwrap_or(&Value::Null)))?;
+            entries.insert(key, value);
+        }
+        Ok(Value::Object(entries))
+    }
+}
+
+/// `string, required, maxLength: 5`
+fn annotation(schema: &Schema, required: bool) -> String {
+    let required = if required { "required" } else { "optional" };
+    [type_name(schema), required.to_owned()]
+        .into_iter()
+        .chain(constraints(schema))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// A value on a single line. JSON is valid YAML in flow style.
+fn flow(value: &Value) -> String {
+    value.to_string()
+}
+
+/// A file in the temporary directory, removed when dropped so no return path leaves it behind.
+struct TempFile(PathBuf);
+
+impl TempFile {
+    /// Creates an empty file with a random name in the temporary directory.
+    /// `create_new` fails instead of following a file or a link put there by someone else.
+    fn create(extension: &str) -> Result<Self, EditorError> {
+        loop {
+            let path = env::temp_dir().join(format!(
+                "oreq-body-{:016x}.{}",
+                rand::random::<u64>(),
+                extension
+            ));
+            let mut options = fs::OpenOptions::new();
+            options.write(true).create_new(true);
+            #[cfg(unix)]
+            std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+            match options.open(&path) {
+                Ok(_) => return Ok(Self(path)),
+                Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => continue,
+                Err(err) => return Err(err.into()),
+            }
+        }
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+/// Runs the editor on the terminal, even when the standard streams are redirected.
+fn open(path: &PathBuf) -> Result<(), EditorError> {
+    let command = env::var("VISUAL")
+        .or_else(|_| env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_owned());
+    let mut args = command.split_whitespace();
+    let program = args.next().unwrap_or("vi");
+
+    let tty = || {
+        fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open("/dev/tty")
+            .map(Stdio::from)
+            .unwrap_or_else(|_| Stdio::inherit())
+    };
+    let status = Command::new(program)
+        .args(args)
+        .arg(path)
+        .stdin(tty())
+        .stdout(tty())
+        .status()
+        .map_err(|x| EditorError::Spawn(command.clone(), x))?;
+    if !status.success() {
+        return Err(EditorError::Exit(command, status));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn temp_file_is_removed() {
+        let temp = TempFile::create("yaml").unwrap();
+        let path = temp.0.clone();
+        assert!(path.exists());
+        drop(temp);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn yaml_numbers_keep_their_text() {
+        let api = OpenAPI::default();
+        let schema = serde_json::from_value::<Schema>(json!({
+            "type": "object",
+            "properties": {
+                "version": {"type": "string"},
+                "tags": {"type": "array", "items": {"type": "string"}},
+                "ratio": {"type": "number"},
+                "count": {"type": "integer"}
+            }
+        }))
+        .unwrap();
+        let editor = Editor::new(&api, &schema, EditorFormat::Yaml);
+        let value = editor
+            .parse("version: 1.10\ntags: [1e2, a]\nratio: 1.10\ncount: 0x1A\nextra: 2.50")
+            .unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "version": "1.10",
+                "tags": ["1e2", "a"],
+                "ratio": 1.1,
+                "count": 26,
+                "extra": 2.5
+            })
+        );
+    }
+}
@@ -1,6 +1,9 @@
 use oreq::schema::error::SchemaError;
 
-use crate::fmt::FormatError;
+use crate::{
+    collection::CollectionError, editor::EditorError, field::FieldError, fmt::FormatError,
+    interpolate::InterpolateError,
+};
 
 #[derive(Debug, thiserror::Error)]
 pub enum AppError {
@@ -8,6 +11,8 @@ pub enum AppError {
     SchemaParseError,
     #[error("No servers in schema")]
     NoServers,
+    #[error("No schema specified. Pass a schema path or set `schema` in .oreq.toml")]
+    NoSchema,
     #[error(transparent)]
     SchemaError(#[from] SchemaError),
     #[error(transparent)]
@@ -18,6 +23,18 @@ pub enum AppError {
     ParseError(#[from] url::ParseError),
     #[error("transparent")]
     FormatError(#[from] FormatError),
+    #[error(transparent)]
+    InterpolateError(#[from] InterpolateError),
+    #[error(transparent)]
+    CollectionError(#[from] CollectionError),
+    #[error(transparent)]
+    FieldError(#[from] FieldError),
+    #[error(transparent)]
+    EditorError(#[from] EditorError),
+    #[error("Cannot build the request without prompting:\n{}", .0.iter().map(|x| format!("  - {}", x)).collect::<Vec<_>>().join("\n"))]
+    Incomplete(Vec<String>),
+    #[error("Invalid values:\n{}", .0.iter().map(|x| format!("  - {}", x)).collect::<Vec<_>>().join("\n"))]
+    InvalidInput(Vec<String>),
 }
 
 impl AppError {
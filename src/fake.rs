@@ -0,0 +1,573 @@
+use std::collections::{hash_map::Entry, HashMap};
+
+use chrono::{Duration, NaiveDate, SecondsFormat};
+use openapiv3::{
+    AdditionalProperties, ArrayType, IntegerType, NumberType, ObjectType, OpenAPI, Schema,
+    SchemaKind, StringType, Type,
+};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use regex::Regex;
+use serde_json::{json, Map, Value};
+
+use crate::{
+    prompts::{array, format::Format, number, number::Numeric, string, utils::escape_strings},
+    schema::{error::SchemaError, pattern, reference::ReferenceOrExt},
+};
+
+/// Required references nested deeper than this are left `null`, so recursive schemas end.
+const MAX_DEPTH: usize = 16;
+/// Optional properties are only generated this close to the top, so the values stay small.
+const OPTIONAL_DEPTH: usize = 4;
+/// The range of numbers bounded on one side or not at all.
+const SPAN: i128 = 100;
+/// How much longer than their minimum strings and arrays without a maximum get.
+const EXTRA_LENGTH: usize = 8;
+const EXTRA_ITEMS: usize = 2;
+/// Tries to generate a string matching both the pattern and the lengths, or an item which is unique.
+const TRIES: usize = 16;
+
+/// Generates values which are valid against a schema: enums, patterns, formats,
+/// bounds, lengths and sizes are followed. The same seed generates the same values.
+pub struct Faker {
+    rng: StdRng,
+    optional: bool,
+    include_read_only: bool,
+    /// Compiled patterns, keyed by the pattern and the longest repetition.
+    generators: HashMap<(String, u32), (Regex, rand_regex::Regex)>,
+    escape: Option<fn(&str) -> String>,
+}
+
+impl Faker {
+    pub fn new(seed: Option<u64>) -> Self {
+        Self {
+            rng: match seed {
+                Some(seed) => StdRng::seed_from_u64(seed),
+                None => StdRng::from_entropy(),
+            },
+            optional: true,
+            include_read_only: false,
+            generators: HashMap::new(),
+            escape: None,
+        }
+    }
+
+    /// Generate optional properties and parameters too, each one at random.
+    pub fn with_optional(&mut self, optional: bool) -> &mut Self {
+        self.optional = optional;
+        self
+    }
+
+    /// Generate readOnly properties too.
+    pub fn with_include_read_only(&mut self, include_read_only: bool) -> &mut Self {
+        self.include_read_only = include_read_only;
+        self
+    }
+
+    /// Applied to the generated strings, e.g. to keep them from being interpolated.
+    /// Strings of the seed are kept as they are.
+    pub fn with_escape(&mut self, escape: Option<fn(&str) -> String>) -> &mut Self {
+        self.escape = escape;
+        self
+    }
+
+    /// Whether a field is generated. Deprecated optional fields never are.
+    pub fn includes(&mut self, required: bool, deprecated: bool) -> bool {
+        required || (self.optional && !deprecated && self.rng.gen_bool(0.5))
+    }
+
+    /// A value of the schema. The values of `seed` are kept, and completed with the fields they miss.
+    pub fn value(
+        &mut self,
+        api: &OpenAPI,
+        schema: &Schema,
+        seed: Option<&Value>,
+    ) -> Result<Value, SchemaError> {
+        self.build(api, schema, seed, 0)
+    }
+
+    fn build(
+        &mut self,
+        api: &OpenAPI,
+        schema: &Schema,
+        seed: Option<&Value>,
+        depth: usize,
+    ) -> Result<Value, SchemaError> {
+        if depth > MAX_DEPTH {
+            return Ok(Value::Null);
+        }
+
+        match (&schema.schema_kind, seed) {
+            (SchemaKind::Type(Type::Object(object)), Some(Value::Object(seed))) => {
+                self.object(api, object, seed, depth)
+            }
+            (SchemaKind::Type(Type::Object(object)), None) => {
+                self.object(api, object, &Map::new(), depth)
+            }
+            (SchemaKind::Type(Type::Array(array)), Some(Value::Array(seed))) => {
+                let Some(items) = &array.items else {
+                    return Ok(json!(seed));
+                };
+                let items = items.clone().unbox();
+                let items = items.item(api)?;
+                seed.iter()
+                    .map(|x| self.build(api, items, Some(x), depth + 1))
+                    .collect::<Result<Vec<_>, _>>()
+                    .map(Value::Array)
+            }
+            (_, Some(seed)) => Ok(seed.clone()),
+            (SchemaKind::Type(Type::Array(array)), None) => self.array(api, array, depth),
+            (SchemaKind::Type(Type::String(x)), None) => {
+                Ok(escape_strings(&self.string(x)?, self.escape))
+            }
+            (SchemaKind::Type(Type::Integer(x)), None) => Ok(self.integer(x)),
+            (SchemaKind::Type(Type::Number(x)), None) => Ok(self.number(x)),
+            (SchemaKind::Type(Type::Boolean(_)), None) => Ok(json!(self.rng.gen_bool(0.5))),
+            (
+                SchemaKind::OneOf { one_of: schemas } | SchemaKind::AnyOf { any_of: schemas },
+                None,
+            ) => match schemas.choose(&mut self.rng) {
+                Some(schema) => {
+                    let schema = schema.item(api)?.clone();
+                    self.build(api, &schema, None, depth + 1)
+                }
+                None => Ok(Value::Null),
+            },
+            // The properties of every schema are merged.
+            (SchemaKind::AllOf { all_of: schemas }, None) => {
+                let mut merged = Map::new();
+                for schema in schemas {
+                    let schema = schema.item(api)?.clone();
+                    match self.build(api, &schema, None, depth + 1)? {
+                        Value::Object(map) => merged.extend(map),
+                        value if schemas.len() == 1 => return Ok(value),
+                        _ => {}
+                    }
+                }
+                Ok(Value::Object(merged))
+            }
+            _ => Ok(json!(self.word(EXTRA_LENGTH))),
+        }
+    }
+
+    fn object(
+        &mut self,
+        api: &OpenAPI,
+        object: &ObjectType,
+        seed: &Map<String, Value>,
+        depth: usize,
+    ) -> Result<Value, SchemaError> {
+        let mut map = Map::new();
+        let mut optional = Vec::new();
+        for (key, property) in &object.properties {
+            let property = property.clone().unbox();
+            let property = property.item(api)?;
+            let required = object.required.contains(key);
+            let data = &property.schema_data;
+            if let Some(value) = seed.get(key) {
+                map.insert(
+                    key.clone(),
+                    self.build(api, property, Some(value), depth + 1)?,
+                );
+                continue;
+            }
+            if data.read_only && !self.include_read_only {
+                continue;
+            }
+            if required || (depth < OPTIONAL_DEPTH && self.includes(false, data.deprecated)) {
+                map.insert(key.clone(), self.build(api, property, None, depth + 1)?);
+                if !required {
+                    optional.push(key.clone());
+                }
+            }
+        }
+        for (key, value) in seed {
+            if !map.contains_key(key) {
+                map.insert(key.clone(), value.clone());
+            }
+        }
+
+        // Optional properties taken at random may be too many or too few.
+        if let Some(max) = object.max_properties {
+            while map.len() > max {
+                let Some(key) = optional.pop() else { break };
+                map.remove(&key);
+            }
+        }
+        let min = object.min_properties.unwrap_or(0);
+        for (key, property) in &object.properties {
+            if map.len() >= min {
+                break;
+            }
+            let property = property.clone().unbox();
+            let property = property.item(api)?;
+            if !map.contains_key(key) && !property.schema_data.read_only {
+                map.insert(key.clone(), self.build(api, property, None, depth + 1)?);
+            }
+        }
+        let mut index = map.len();
+        while map.len() < min {
+            index += 1;
+            let value = match &object.additional_properties {
+                Some(AdditionalProperties::Any(false)) => break,
+                Some(AdditionalProperties::Schema(schema)) => {
+                    let schema = schema.item(api)?.clone();
+                    self.build(api, &schema, None, depth + 1)?
+                }
+                _ => json!(self.word(EXTRA_LENGTH)),
+            };
+            map.insert(format!("key{}", index), value);
+        }
+
+        Ok(Value::Object(map))
+    }
+
+    fn array(
+        &mut self,
+        api: &OpenAPI,
+        array: &ArrayType,
+        depth: usize,
+    ) -> Result<Value, SchemaError> {
+        let option = array::PromptOption::new(array.clone());
+        let min = option.min_items().unwrap_or(0);
+        let max = option.max_items().unwrap_or(min + EXTRA_ITEMS + 1).max(min);
+        // One item at least shows the shape of the items, where it is allowed.
+        let low = min.max(1).min(max);
+        let len = self.rng.gen_range(low..=max.min(low + EXTRA_ITEMS));
+
+        let schema = match &array.items {
+            Some(items) => Some(items.clone().unbox().item(api)?.clone()),
+            None => None,
+        };
+        let mut items = Vec::new();
+        for _ in 0..len {
+            for _ in 0..TRIES {
+                let item = match &schema {
+                    Some(schema) => self.build(api, schema, None, depth + 1)?,
+                    None => json!(self.word(EXTRA_LENGTH)),
+                };
+                if !option.unique_items() || !items.contains(&item) {
+                    items.push(item);
+                    break;
+                }
+            }
+        }
+
+        Ok(Value::Array(items))
+    }
+
+    fn string(&mut self, string: &StringType) -> Result<Value, SchemaError> {
+        let option = string::PromptOption::from(string.clone());
+        let options = option.enumeration().cloned().collect::<Vec<_>>();
+        if let Some(value) = options.choose(&mut self.rng) {
+            return Ok(json!(value));
+        }
+        if let Some(format) = option.format() {
+            return Ok(json!(self.format(format)));
+        }
+
+        let min = option.min_length().unwrap_or(0);
+        // Without `maxLength`, the pattern alone bounds the length of its values.
+        // A pattern which is not checked is not followed either.
+        if let Some(pattern) = option.pattern().filter(|_| option.checks_pattern()) {
+            let max = option.max_length().map(|x| x.max(min));
+            let repeat = max.map_or(min + EXTRA_LENGTH, |max| (min + EXTRA_LENGTH).min(max));
+            let (regex, generator) = Self::generator(&mut self.generators, pattern, repeat)?;
+            for _ in 0..TRIES {
+                let value: String = self.rng.sample(generator);
+                let length = value.chars().count();
+                if regex.is_match(&value) && length >= min && max.is_none_or(|max| length <= max) {
+                    return Ok(json!(value));
+                }
+            }
+            return Err(SchemaError::UnsatisfiablePattern {
+                pattern: pattern.to_owned(),
+                min,
+                max,
+            });
+        }
+
+        let max = option
+            .max_length()
+            .unwrap_or(min.max(1) + EXTRA_LENGTH)
+            .max(min);
+
+        let low = min.max(1).min(max);
+        let len = self.rng.gen_range(low..=max.min(low + EXTRA_LENGTH));
+        Ok(json!(self.word(len)))
+    }
+
+    /// The pattern compiled to check values and to generate them, once for each pattern.
+    /// Repetitions are generated up to `repeat` times, so long minimum lengths are reached.
+    fn generator<'a>(
+        generators: &'a mut HashMap<(String, u32), (Regex, rand_regex::Regex)>,
+        pattern: &str,
+        repeat: usize,
+    ) -> Result<&'a (Regex, rand_regex::Regex), SchemaError> {
+        let repeat = repeat.clamp(1, u32::MAX as usize) as u32;
+        match generators.entry((pattern.to_owned(), repeat)) {
+            Entry::Occupied(entry) => Ok(entry.into_mut()),
+            Entry::Vacant(entry) => {
+                let invalid = |message: String| SchemaError::InvalidPattern {
+                    path: "fake".to_owned(),
+                    pattern: pattern.to_owned(),
+                    message,
+                };
+                let regex = pattern::compile(pattern).map_err(|err| invalid(err.to_string()))?;
+                let translated =
+                    pattern::translate(pattern).map_err(|err| invalid(err.to_string()))?;
+                let generator = rand_regex::Regex::compile(&unanchor(&translated), repeat)
+                    .map_err(|err| invalid(err.to_string()))?;
+                Ok(entry.insert((regex, generator)))
+            }
+        }
+    }
+
+    fn format(&mut self, format: Format) -> String {
+        // Dates between 2000 and 2030, so the same seed gives the same dates.
+        let start = NaiveDate::from_ymd_opt(2000, 1, 1)
+            .unwrap_or_default()
+            .and_hms_opt(0, 0, 0)
+            .unwrap_or_default()
+            .and_utc();
+        let at = |seconds: i64| start + Duration::seconds(seconds);
+        match format {
+            Format::Date => at(self.rng.gen_range(0..30 * 365) * 86400)
+                .format("%Y-%m-%d")
+                .to_string(),
+            Format::DateTime => at(self.rng.gen_range(0..30 * 365 * 86400))
+                .to_rfc3339_opts(SecondsFormat::Secs, true),
+            Format::Uuid => uuid::Builder::from_random_bytes(self.rng.gen())
+                .into_uuid()
+                .to_string(),
+            Format::Email => format!("{}@example.com", self.word(EXTRA_LENGTH)),
+            Format::Uri => format!("https://example.com/{}", self.word(EXTRA_LENGTH)),
+            Format::Hostname => format!("{}.example.com", self.word(EXTRA_LENGTH)),
+            Format::Ipv4 => {
+                let [a, b, c, d] = self.rng.gen::<[u8; 4]>();
+                format!("{}.{}.{}.{}", a, b, c, d)
+            }
+            Format::Ipv6 => {
+                let [a, b] = self.rng.gen::<[u16; 2]>();
+                format!("2001:db8::{:x}:{:x}", a, b)
+            }
+        }
+    }
+
+    fn integer(&mut self, integer: &IntegerType) -> Value {
+        let option = number::PromptOption::<i128>::from(integer.clone());
+        let options = option.enumeration().cloned().collect::<Vec<_>>();
+        if let Some(value) = options.choose(&mut self.rng) {
+            return value.to_json();
+        }
+
+        let min = option.minimum().map(|(x, exclusive)| x + exclusive as i128);
+        let max = option.maximum().map(|(x, exclusive)| x - exclusive as i128);
+        let (mut min, mut max) = match (min, max) {
+            (Some(min), Some(max)) => (min, max),
+            (Some(min), None) => (min, min.saturating_add(SPAN)),
+            (None, Some(max)) => (max.saturating_sub(SPAN), max),
+            (None, None) => (1, SPAN),
+        };
+        if let Some((low, high, _)) = option.format() {
+            min = min.max(*low);
+            max = max.min(*high);
+        }
+        if min > max {
+            return min.to_json();
+        }
+
+        let value = match option.multiple_of().filter(|x| *x > 0) {
+            Some(step) => {
+                let first = min.div_euclid(step) + (min.rem_euclid(step) != 0) as i128;
+                let last = max.div_euclid(step);
+                if first > last {
+                    min
+                } else {
+                    self.rng.gen_range(first..=last) * step
+                }
+            }
+            None => self.rng.gen_range(min..=max),
+        };
+        value.to_json()
+    }
+
+    fn number(&mut self, number: &NumberType) -> Value {
+        let option = number::PromptOption::<f64>::from(number.clone());
+        let options = option.enumeration().cloned().collect::<Vec<_>>();
+        if let Some(value) = options.choose(&mut self.rng) {
+            return value.to_json();
+        }
+
+        let span = SPAN as f64;
+        let (min, max) = match (option.minimum(), option.maximum()) {
+            (Some((min, _)), Some((max, _))) => (min, max),
+            (Some((min, _)), None) => (min, min + span),
+            (None, Some((max, _))) => (max - span, max),
+            (None, None) => (0.0, span),
+        };
+        let fits = |x: f64| {
+            option
+                .minimum()
+                .is_none_or(|(min, exclusive)| x > min || (!exclusive && x == min))
+                && option
+                    .maximum()
+                    .is_none_or(|(max, exclusive)| x < max || (!exclusive && x == max))
+                && option
+                    .format()
+                    .is_none_or(|(low, high, _)| (*low..=*high).contains(&x))
+        };
+
+        if let Some(step) = option.multiple_of().filter(|x| *x > 0.0) {
+            let first = (min / step).ceil() as i64;
+            let last = (max / step).floor() as i64;
+            let candidates = (first..=last.min(first + SPAN as i64))
+                .map(|x| round(x as f64 * step, step))
+                .filter(|x| fits(*x))
+                .collect::<Vec<_>>();
+            return json!(candidates.choose(&mut self.rng).copied().unwrap_or(min));
+        }
+
+        let value = round(self.rng.gen_range(min..=max), 0.01);
+        match fits(value) {
+            true => json!(value),
+            false => json!((min + max) / 2.0),
+        }
+    }
+
+    fn word(&mut self, len: usize) -> String {
+        (0..len)
+            .map(|_| self.rng.gen_range(b'a'..=b'z') as char)
+            .collect()
+    }
+}
+
+/// Rounds to the decimals of `step`, so `3 * 0.1` is `0.3`.
+fn round(value: f64, step: f64) -> f64 {
+    let decimals = step
+        .to_string()
+        .split_once('.')
+        .map_or(0, |(_, fraction)| fraction.len());
+    format!("{:.*}", decimals, value).parse().unwrap_or(value)
+}
+
+/// Anchors and word boundaries are not supported by the generator. They are left out,
+/// and the generated value is checked against the whole pattern afterwards.
+fn unanchor(pattern: &str) -> String {
+    let pattern = pattern.replace(r"(?-u:\b)", "").replace(r"(?-u:\B)", "");
+    let pattern = pattern.strip_prefix('^').unwrap_or(&pattern);
+    match pattern.strip_suffix('$') {
+        Some(rest) if !rest.ends_with('\\') => rest.to_owned(),
+        _ => pattern.to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::validate::validate;
+
+    fn api() -> OpenAPI {
+        serde_json::from_value(json!({
+            "openapi": "3.0.0",
+            "info": {"title": "", "version": ""},
+            "paths": {},
+            "components": {"schemas": {
+                "Tag": {"type": "string", "enum": ["a", "b", "c"]}
+            }}
+        }))
+        .unwrap()
+    }
+
+    fn schema() -> Schema {
+        serde_json::from_value(json!({
+            "type": "object",
+            "required": ["id", "code", "sku", "qty", "price", "tags", "when", "mail", "owner", "meta"],
+            "properties": {
+                "id": {"type": "string", "format": "uuid"},
+                "code": {"type": "string", "pattern": "^[A-Z]{3}-\\d{4}$"},
+                "sku": {"type": "string", "pattern": "^sku_[a-z0-9]+_[a-z0-9]+_[a-z0-9]+$"},
+                "name": {"type": "string", "minLength": 3, "maxLength": 5},
+                "qty": {"type": "integer", "format": "int32", "minimum": 10, "exclusiveMinimum": true, "multipleOf": 5},
+                "price": {"type": "number", "minimum": 0, "maximum": 1, "multipleOf": 0.05},
+                "tags": {
+                    "type": "array",
+                    "items": {"$ref": "#/components/schemas/Tag"},
+                    "minItems": 2,
+                    "uniqueItems": true
+                },
+                "when": {"type": "string", "format": "date-time"},
+                "mail": {"type": "string", "format": "email"},
+                "owner": {
+                    "type": "object",
+                    "required": ["name"],
+                    "properties": {
+                        "name": {"type": "string"},
+                        "id": {"type": "integer", "readOnly": true}
+                    }
+                },
+                "meta": {
+                    "type": "object",
+                    "additionalProperties": {"type": "integer", "minimum": 1},
+                    "minProperties": 2
+                }
+            }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn values_are_valid() {
+        let (api, schema) = (api(), schema());
+        for seed in 0..50 {
+            let value = Faker::new(Some(seed)).value(&api, &schema, None).unwrap();
+            let violations = validate(&api, &schema, &value, "body").unwrap();
+            assert!(violations.is_empty(), "{value}: {violations:?}");
+            assert!(value["owner"].get("id").is_none(), "{value}");
+        }
+    }
+
+    #[test]
+    fn same_seed_same_values() {
+        let (api, schema) = (api(), schema());
+        let value = |seed| Faker::new(Some(seed)).value(&api, &schema, None).unwrap();
+        assert_eq!(value(7), value(7));
+        assert_ne!(value(7), value(8));
+    }
+
+    #[test]
+    fn seed_is_kept() {
+        let (api, schema) = (api(), schema());
+        let mut faker = Faker::new(Some(1));
+        faker.with_escape(Some(|x: &str| format!("<{}>", x)));
+        let value = faker
+            .value(
+                &api,
+                &schema,
+                Some(&json!({"owner": {"nick": "x"}, "code": "$1"})),
+            )
+            .unwrap();
+        assert_eq!(value["code"], "$1");
+        assert_eq!(value["owner"]["nick"], "x");
+        assert!(value["owner"]["name"].as_str().unwrap().starts_with('<'));
+    }
+
+    #[test]
+    fn unsatisfiable_pattern() {
+        let api = api();
+        let schema = serde_json::from_value::<Schema>(json!({
+            "type": "string", "pattern": "^a{10}$", "maxLength": 5
+        }))
+        .unwrap();
+        let err = Faker::new(Some(1)).value(&api, &schema, None).unwrap_err();
+        assert!(matches!(
+            err,
+            SchemaError::UnsatisfiablePattern {
+                min: 0,
+                max: Some(5),
+                ..
+            }
+        ));
+    }
+}
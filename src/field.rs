@@ -0,0 +1,266 @@
+use std::{fmt::Display, fs, str::FromStr};
+
+use serde_json::{json, Value};
+
+use crate::interpolate::{self, InterpolateError};
+
+#[derive(Debug, thiserror::Error)]
+pub enum FieldError {
+    #[error("Invalid field path '{0}'")]
+    InvalidPath(String),
+    #[error("Missing '=' in '{0}'")]
+    MissingValue(String),
+    #[error("Invalid JSON for '{0}': {1}")]
+    InvalidJson(String, serde_json::Error),
+    #[error("Failed to read {0}: {1}")]
+    ReadFile(String, std::io::Error),
+    #[error("'{0}' is not an object")]
+    NotObject(String),
+    #[error("'{0}' is not an array")]
+    NotArray(String),
+    #[error("'{0}[{1}]' is set before the previous items")]
+    OutOfRange(String, usize),
+    #[error(transparent)]
+    Interpolate(#[from] InterpolateError),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Segment {
+    Key(String),
+    Index(usize),
+    /// `[]`, a new item at the end of the array.
+    Append,
+}
+
+impl Display for Segment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Segment::Key(key) => write!(f, ".{}", key),
+            Segment::Index(idx) => write!(f, "[{}]", idx),
+            Segment::Append => write!(f, "[]"),
+        }
+    }
+}
+
+/// A body field given on the command line, e.g. `user.address.city=Tokyo`,
+/// `tags[]=a`, `items[0].qty=2` or `meta:=@meta.json`.
+///
+/// `=` takes JSON and falls back to a string, `:=` takes JSON only and
+/// `:=@file` reads the JSON from the file. `=@file` reads the file as a string
+/// at any depth, and `=\@` is a literal `@`.
+#[derive(Debug, Clone)]
+pub struct Field {
+    pub path: Vec<Segment>,
+    pub value: Value,
+}
+
+impl FromStr for Field {
+    type Err = FieldError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (key, value) = s
+            .split_once('=')
+            .ok_or_else(|| FieldError::MissingValue(s.to_owned()))?;
+        let (key, raw) = match key.strip_suffix(':') {
+            Some(key) => (key.trim(), true),
+            None => (key.trim(), false),
+        };
+        let value = value.trim();
+        let path = parse_path(key)?;
+
+        let value = if raw {
+            let content = match value.strip_prefix('@') {
+                Some(file) => fs::read_to_string(file)
+                    .map_err(|err| FieldError::ReadFile(file.to_owned(), err))?,
+                None => value.to_owned(),
+            };
+            serde_json::from_str::<Value>(&content)
+                .map_err(|err| FieldError::InvalidJson(key.to_owned(), err))?
+        } else {
+            interpolate::flag_value(value)?
+        };
+
+        Ok(Self { path, value })
+    }
+}
+
+fn parse_path(s: &str) -> Result<Vec<Segment>, FieldError> {
+    let invalid = || FieldError::InvalidPath(s.to_owned());
+
+    let mut path = Vec::new();
+    for (i, part) in s.split('.').enumerate() {
+        let (key, mut rest) = match part.find('[') {
+            Some(idx) => part.split_at(idx),
+            None => (part, ""),
+        };
+        // Only the first segment may start with an index, for array bodies.
+        if !key.is_empty() {
+            path.push(Segment::Key(key.to_owned()));
+        } else if i > 0 || rest.is_empty() {
+            return Err(invalid());
+        }
+
+        while !rest.is_empty() {
+            let end = rest.find(']').ok_or_else(invalid)?;
+            let index = rest.strip_prefix('[').ok_or_else(invalid)?;
+            let index = &index[..end - 1];
+            path.push(if index.is_empty() {
+                Segment::Append
+            } else {
+                Segment::Index(index.parse().map_err(|_| invalid())?)
+            });
+            rest = &rest[end + 1..];
+        }
+    }
+
+    Ok(path)
+}
+
+/// Sets the field in the body, creating the objects and the arrays on the way.
+pub fn assign(body: &mut Value, field: &Field) -> Result<(), FieldError> {
+    let mut target = body;
+    let mut name = String::new();
+    for segment in &field.path {
+        if target.is_null() {
+            *target = match segment {
+                Segment::Key(_) => json!({}),
+                _ => json!([]),
+            };
+        }
+
+        target = match segment {
+            Segment::Key(key) => target
+                .as_object_mut()
+                .ok_or_else(|| FieldError::NotObject(name.clone()))?
+                .entry(key.clone())
+                .or_insert(Value::Null),
+            Segment::Index(idx) => {
+                let items = target
+                    .as_array_mut()
+                    .ok_or_else(|| FieldError::NotArray(name.clone()))?;
+                match idx.cmp(&items.len()) {
+                    std::cmp::Ordering::Less => {}
+                    std::cmp::Ordering::Equal => items.push(Value::Null),
+                    std::cmp::Ordering::Greater => {
+                        return Err(FieldError::OutOfRange(name.clone(), *idx))
+                    }
+                }
+                &mut items[*idx]
+            }
+            Segment::Append => {
+                let items = target
+                    .as_array_mut()
+                    .ok_or_else(|| FieldError::NotArray(name.clone()))?;
+                items.push(Value::Null);
+                items.last_mut().unwrap()
+            }
+        };
+        name.push_str(&segment.to_string());
+        if name.starts_with('.') {
+            name.remove(0);
+        }
+    }
+
+    *target = field.value.clone();
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(key: &str) -> Segment {
+        Segment::Key(key.to_owned())
+    }
+
+    #[test]
+    fn parse_path_segments() {
+        assert_eq!(parse_path("name").unwrap(), [key("name")]);
+        assert_eq!(
+            parse_path("user.address.city").unwrap(),
+            [key("user"), key("address"), key("city")]
+        );
+        assert_eq!(
+            parse_path("items[0].qty").unwrap(),
+            [key("items"), Segment::Index(0), key("qty")]
+        );
+        assert_eq!(
+            parse_path("matrix[1][]").unwrap(),
+            [key("matrix"), Segment::Index(1), Segment::Append]
+        );
+        assert_eq!(
+            parse_path("[2].id").unwrap(),
+            [Segment::Index(2), key("id")]
+        );
+        assert_eq!(parse_path("[]").unwrap(), [Segment::Append]);
+    }
+
+    #[test]
+    fn parse_path_invalid() {
+        for path in [
+            "", "a.", ".a", "a..b", "a.[0]", "a[", "a[0", "a[x]", "a[-1]", "a[0]b", "a[]]",
+        ] {
+            assert!(
+                matches!(parse_path(path), Err(FieldError::InvalidPath(_))),
+                "{path}"
+            );
+        }
+    }
+
+    #[test]
+    fn assign_fields() {
+        let mut body = Value::Null;
+        for field in ["user.name=Ann", "tags[]=a", "tags[]=b", "items[0].qty=2"] {
+            assign(&mut body, &field.parse().unwrap()).unwrap();
+        }
+        assert_eq!(
+            body,
+            json!({"user": {"name": "Ann"}, "tags": ["a", "b"], "items": [{"qty": 2}]})
+        );
+
+        assert!(matches!(
+            assign(&mut body, &"items[2].qty=1".parse().unwrap()),
+            Err(FieldError::OutOfRange(..))
+        ));
+        assert!(matches!(
+            assign(&mut body, &"user[0]=1".parse().unwrap()),
+            Err(FieldError::NotArray(..))
+        ));
+        assert!(matches!(
+            assign(&mut body, &"tags.x=1".parse().unwrap()),
+            Err(FieldError::NotObject(..))
+        ));
+    }
+
+    #[test]
+    fn field_values() {
+        let value = |s: &str| s.parse::<Field>().unwrap().value;
+        assert_eq!(value("qty=2"), json!(2));
+        assert_eq!(value("name=Ann"), json!("Ann"));
+        assert_eq!(value("name = Ann "), json!("Ann"));
+        assert_eq!(value(r#"meta:={"a":[1]}"#), json!({"a": [1]}));
+        assert_eq!(value(r"name=\@ann"), json!("@ann"));
+        assert_eq!(value(r"user.name=\@ann"), json!("@ann"));
+        assert!(matches!(
+            "user.name=@/nonexistent/ann".parse::<Field>(),
+            Err(FieldError::Interpolate(InterpolateError::ReadFile(..)))
+        ));
+
+        let path = std::env::temp_dir().join(format!("oreq-field-{}", std::process::id()));
+        fs::write(&path, "Ann\n").unwrap();
+        let nested = value(&format!("user.name=@{}", path.display()));
+        let top = value(&format!("name=@{}", path.display()));
+        fs::remove_file(&path).unwrap();
+        assert_eq!(nested, json!("Ann"));
+        assert_eq!(top, json!("Ann"));
+        assert!(matches!(
+            "meta:=x".parse::<Field>(),
+            Err(FieldError::InvalidJson(..))
+        ));
+        assert!(matches!(
+            "name".parse::<Field>(),
+            Err(FieldError::MissingValue(_))
+        ));
+    }
+}
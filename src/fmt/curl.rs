@@ -1,29 +1,69 @@
 use url::Url;
 
-use crate::req::{ParamsValue, RequestInit};
+use crate::{
+    interpolate::Reference,
+    req::{ParamsValue, RequestInit},
+};
 
 use super::{FormatError, RequestFormatter};
 
 #[derive(Debug, Clone)]
 pub(crate) struct CurlFormatter;
 
-impl RequestFormatter for CurlFormatter {
-    fn format(&self, req: &RequestInit) -> Result<std::string::String, FormatError> {
-        let mut args = vec![];
+impl CurlFormatter {
+    /// The options of the request, each with its value before quoting.
+    fn options(req: &RequestInit) -> Result<Vec<(Option<&'static str>, String)>, FormatError> {
+        let mut options = vec![];
 
-        args.push(format!("-X {}", req.method));
+        options.push((Some("-X"), req.method.clone()));
         let url: Url = req.clone().try_into()?;
-        args.push(format!("'{}'", url));
+        options.push((None, url.to_string()));
 
         for (k, v) in req.header.iter() {
             let v: ParamsValue = v.clone().into();
-            args.push(format!("-H '{}: {}'", k, v));
+            options.push((Some("-H"), format!("{}: {}", k, v)));
         }
 
         if let Some(body) = &req.body {
-            args.push(format!("-d '{}'", body));
+            options.push((Some("-d"), body.to_string()));
         }
 
+        Ok(options)
+    }
+
+    /// The arguments to run curl with, without a shell.
+    pub fn args(req: &RequestInit) -> Result<Vec<String>, FormatError> {
+        Ok(Self::options(req)?
+            .into_iter()
+            .flat_map(|(flag, value)| flag.map(str::to_owned).into_iter().chain([value]))
+            .collect())
+    }
+}
+
+/// Quotes the value for a POSIX shell.
+fn quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+impl RequestFormatter for CurlFormatter {
+    fn format(&self, req: &RequestInit) -> Result<std::string::String, FormatError> {
+        let args = Self::options(req)?
+            .into_iter()
+            .map(|(flag, value)| match flag {
+                Some("-X") => format!("-X {}", value),
+                Some(flag) => format!("{} {}", flag, quote(&value)),
+                None => quote(&value),
+            })
+            .collect::<Vec<_>>();
+
         Ok(args.join(" "))
     }
+
+    fn reference(&self, reference: &Reference) -> String {
+        match reference {
+            Reference::Env(name) => format!("'\"${}\"'", name),
+            Reference::File(path) => format!("'\"$(cat {})\"'", quote(&path.display().to_string())),
+            Reference::Command(command) => format!("'\"$({})\"'", command),
+        }
+    }
 }
@@ -1,7 +1,10 @@
 use indoc::formatdoc;
 use url::Url;
 
-use crate::req::{ParamsValue, RequestInit};
+use crate::{
+    interpolate::Reference,
+    req::{ParamsValue, RequestInit},
+};
 
 use super::{FormatError, RequestFormatter};
 
@@ -47,4 +50,14 @@ impl RequestFormatter for FetchFormatter {
         }
         .to_owned())
     }
+
+    fn reference(&self, reference: &Reference) -> String {
+        let placeholder = match reference {
+            Reference::Env(name) => return format!("' + process.env.{} + '", name),
+            Reference::File(path) => format!("<content of {}>", path.display()),
+            Reference::Command(command) => format!("<output of $({})>", command),
+        };
+
+        placeholder.replace('\\', "\\\\").replace('\'', "\\'")
+    }
 }
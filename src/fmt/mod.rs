@@ -2,7 +2,7 @@ use std::str::FromStr;
 
 use clap::ValueEnum;
 
-use crate::req::RequestInit;
+use crate::{interpolate::Reference, req::RequestInit};
 
 pub(crate) mod curl;
 pub(crate) mod fetch;
@@ -23,6 +23,10 @@ pub enum FromStrError {
 
 pub(crate) trait RequestFormatter {
     fn format(&self, req: &RequestInit) -> Result<String, FormatError>;
+    /// Expression which evaluates the reference at run time, used by `--keep-vars`,
+    /// or a placeholder when the format cannot evaluate it.
+    /// The expression is spliced into a single-quoted string.
+    fn reference(&self, reference: &Reference) -> String;
 }
 
 #[derive(Debug, Clone, ValueEnum)]
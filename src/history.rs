@@ -0,0 +1,245 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::PathBuf,
+};
+
+use anyhow::{anyhow, Context as _};
+use chrono::{DateTime, Local};
+use fuzzy_matcher::{skim::SkimMatcherV2, FuzzyMatcher};
+use serde::{Deserialize, Serialize};
+
+use crate::{answers::Answered, req::RequestInit};
+
+/// A completed request.
+///
+/// The request is stored before interpolation, so secrets referenced by
+/// `${VAR}` or `$(command)` are not written to the history.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub id: usize,
+    pub timestamp: DateTime<Local>,
+    pub schema: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub env: Option<String>,
+    #[serde(flatten)]
+    pub answered: Answered,
+    pub request: RequestInit,
+}
+
+impl HistoryEntry {
+    pub fn label(&self) -> String {
+        format!("{} {}", self.answered.method, self.answered.path)
+    }
+
+    pub fn hint(&self) -> String {
+        format!(
+            "#{} {} {}",
+            self.id,
+            self.timestamp.format("%Y-%m-%d %H:%M"),
+            self.schema
+        )
+    }
+}
+
+pub struct History {
+    path: PathBuf,
+}
+
+impl History {
+    /// Opens the history file. `OREQ_HISTORY` overrides the default location.
+    pub fn open() -> anyhow::Result<Self> {
+        let path = match std::env::var_os("OREQ_HISTORY") {
+            Some(path) => PathBuf::from(path),
+            None => dirs::data_dir()
+                .ok_or_else(|| anyhow!("Failed to find the data directory"))?
+                .join("oreq")
+                .join("history.jsonl"),
+        };
+
+        Ok(Self { path })
+    }
+
+    /// Lines which cannot be parsed are reported and skipped, so one broken
+    /// entry does not hide the others.
+    pub fn entries(&self) -> anyhow::Result<Vec<HistoryEntry>> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let file = fs::File::open(&self.path)?;
+        let mut entries = Vec::new();
+        for (idx, line) in BufReader::new(file).lines().enumerate() {
+            let line = line.with_context(|| format!("Failed to read {}", self.path.display()))?;
+            match serde_json::from_str::<HistoryEntry>(&line) {
+                Ok(entry) => entries.push(entry),
+                Err(err) => eprintln!(
+                    "Warning: skipped line {} of {}: {}",
+                    idx + 1,
+                    self.path.display(),
+                    err
+                ),
+            }
+        }
+
+        Ok(entries)
+    }
+
+    pub fn get(&self, id: usize) -> anyhow::Result<HistoryEntry> {
+        self.entries()?
+            .into_iter()
+            .find(|x| x.id == id)
+            .ok_or_else(|| anyhow!("History #{} not found", id))
+    }
+
+    pub fn search(&self, query: &str) -> anyhow::Result<Vec<HistoryEntry>> {
+        let matcher = SkimMatcherV2::default();
+        let entries = self
+            .entries()?
+            .into_iter()
+            .filter(|x| {
+                let text = format!("{} {}", x.label(), x.hint());
+                matcher.fuzzy_match(&text, query).is_some()
+            })
+            .collect();
+
+        Ok(entries)
+    }
+
+    pub fn append(
+        &self,
+        schema: String,
+        env: Option<String>,
+        answered: Answered,
+        request: RequestInit,
+    ) -> anyhow::Result<HistoryEntry> {
+        let id = self.entries()?.last().map(|x| x.id + 1).unwrap_or(1);
+        let entry = HistoryEntry {
+            id,
+            timestamp: Local::now(),
+            schema,
+            env,
+            answered,
+            request,
+        };
+
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .with_context(|| format!("Failed to open {}", self.path.display()))?;
+        writeln!(file, "{}", serde_json::to_string(&entry)?)?;
+
+        Ok(entry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn history(name: &str) -> History {
+        let path = std::env::temp_dir().join(format!(
+            "oreq-history-{}-{}.jsonl",
+            std::process::id(),
+            name
+        ));
+        let _ = fs::remove_file(&path);
+        History { path }
+    }
+
+    fn answered(path: &str) -> Answered {
+        serde_json::from_value(json!({
+            "method": "POST",
+            "path": path,
+            "operation_id": "createPet",
+            "answers": {"header": {"X-Token": "${TOKEN}"}, "body": {"name": "Tama"}}
+        }))
+        .unwrap()
+    }
+
+    fn request(path: &str) -> RequestInit {
+        RequestInit {
+            method: "POST".to_owned(),
+            base: "https://api.example.com".to_owned(),
+            path: path.to_owned(),
+            query: vec![("dry".to_owned(), None)],
+            header: vec![("X-Token".to_owned(), json!("${TOKEN}"))],
+            cookie: Vec::new(),
+            body: Some(json!({"name": "Tama"})),
+        }
+    }
+
+    #[test]
+    fn append_and_read_back() {
+        let history = history("round-trip");
+        assert!(history.entries().unwrap().is_empty());
+
+        let first = history
+            .append(
+                "api.yaml".to_owned(),
+                Some("dev".to_owned()),
+                answered("/pets"),
+                request("/pets"),
+            )
+            .unwrap();
+        history
+            .append(
+                "api.yaml".to_owned(),
+                None,
+                answered("/owners"),
+                request("/owners"),
+            )
+            .unwrap();
+
+        let entries = history.entries().unwrap();
+        assert_eq!(entries.iter().map(|x| x.id).collect::<Vec<_>>(), [1, 2]);
+        let entry = history.get(1).unwrap();
+        assert_eq!(entry.timestamp, first.timestamp);
+        assert_eq!(entry.env.as_deref(), Some("dev"));
+        assert_eq!(entry.label(), "POST /pets");
+        assert_eq!(entry.answered.operation_id.as_deref(), Some("createPet"));
+        assert_eq!(entry.answered.answers.header["X-Token"], json!("${TOKEN}"));
+        assert_eq!(entry.request.query, [("dry".to_owned(), None)]);
+        assert_eq!(entry.request.body, Some(json!({"name": "Tama"})));
+        assert!(history.get(3).is_err());
+
+        let found = history.search("owners").unwrap();
+        assert_eq!(found.iter().map(|x| x.id).collect::<Vec<_>>(), [2]);
+
+        fs::remove_file(&history.path).unwrap();
+    }
+
+    #[test]
+    fn broken_lines_are_skipped() {
+        let history = history("broken");
+        history
+            .append(
+                "api.yaml".to_owned(),
+                None,
+                answered("/pets"),
+                request("/pets"),
+            )
+            .unwrap();
+        let mut file = OpenOptions::new().append(true).open(&history.path).unwrap();
+        writeln!(file, "{{\"id\": ").unwrap();
+        history
+            .append(
+                "api.yaml".to_owned(),
+                None,
+                answered("/pets"),
+                request("/pets"),
+            )
+            .unwrap();
+
+        let entries = history.entries().unwrap();
+        assert_eq!(entries.iter().map(|x| x.id).collect::<Vec<_>>(), [1, 2]);
+
+        fs::remove_file(&history.path).unwrap();
+    }
+}
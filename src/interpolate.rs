@@ -0,0 +1,496 @@
+use std::{fs, path::PathBuf, process::Command, sync::LazyLock};
+
+use regex::Regex;
+use serde_json::Value;
+
+use crate::req::RequestInit;
+use oreq::validate::{self, Violation};
+
+static TEMPLATE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\{\{\s*env\.([A-Za-z_][A-Za-z0-9_]*)\s*\}\}").unwrap());
+
+#[derive(Debug, thiserror::Error)]
+pub enum InterpolateError {
+    #[error("Environment variable '{0}' is not set")]
+    NotPresent(String),
+    #[error("Failed to read {0}: {1}")]
+    ReadFile(PathBuf, std::io::Error),
+    #[error("Command failed: {0}")]
+    Command(String),
+    #[error("Unterminated expression: {0}")]
+    Unterminated(String),
+}
+
+/// Reads `@path` given as a whole flag value, so prompted values and strings
+/// inside JSON starting with `@` are kept as they are. `\@` escapes a literal `@`.
+/// Other values are parsed as JSON, falling back to a string.
+pub fn flag_value(value: &str) -> Result<Value, InterpolateError> {
+    if let Some(path) = value.strip_prefix('@') {
+        return fs::read_to_string(path)
+            .map(|x| Value::String(x.trim_end().to_owned()))
+            .map_err(|err| InterpolateError::ReadFile(path.into(), err));
+    }
+    if let Some(rest) = value.strip_prefix("\\@") {
+        return Ok(Value::String(format!("@{}", rest)));
+    }
+
+    Ok(validate::parse_text(value))
+}
+
+/// A value which is resolved when the request is built.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Reference {
+    /// `${VAR}` or `{{ env.VAR }}`
+    Env(String),
+    /// A file of a credential in `.oreq.toml`
+    File(PathBuf),
+    /// `$(command)`
+    Command(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Literal(String),
+    Reference(Reference),
+}
+
+impl Reference {
+    fn resolve(&self) -> Result<String, InterpolateError> {
+        match self {
+            Reference::Env(name) => {
+                std::env::var(name).map_err(|_| InterpolateError::NotPresent(name.clone()))
+            }
+            Reference::File(path) => fs::read_to_string(path)
+                .map(|x| x.trim_end().to_owned())
+                .map_err(|err| InterpolateError::ReadFile(path.clone(), err)),
+            Reference::Command(command) => {
+                let output = Command::new("sh")
+                    .arg("-c")
+                    .arg(command)
+                    .output()
+                    .map_err(|_| InterpolateError::Command(command.clone()))?;
+                if !output.status.success() {
+                    return Err(InterpolateError::Command(command.clone()));
+                }
+                Ok(String::from_utf8_lossy(&output.stdout)
+                    .trim_end()
+                    .to_owned())
+            }
+        }
+    }
+}
+
+fn parse(value: &str) -> Result<Vec<Segment>, InterpolateError> {
+    let mut segments = Vec::new();
+    let mut literal = String::new();
+    let mut rest = value;
+
+    while let Some(c) = rest.chars().next() {
+        let reference = if let Some(escaped) = rest.strip_prefix("\\$") {
+            literal.push('$');
+            rest = escaped;
+            continue;
+        } else if let Some(escaped) = rest.strip_prefix("\\{{") {
+            literal.push_str("{{");
+            rest = escaped;
+            continue;
+        } else if let Some(body) = rest.strip_prefix("${") {
+            let end = body
+                .find('}')
+                .ok_or_else(|| InterpolateError::Unterminated(value.to_owned()))?;
+            rest = &body[end + 1..];
+            Reference::Env(body[..end].trim().to_owned())
+        } else if let Some(body) = rest.strip_prefix("$(") {
+            let end = find_closing_paren(body)
+                .ok_or_else(|| InterpolateError::Unterminated(value.to_owned()))?;
+            rest = &body[end + 1..];
+            Reference::Command(body[..end].to_owned())
+        } else if let Some(captures) = TEMPLATE.captures(rest) {
+            let whole = captures.get(0).unwrap();
+            let name = captures[1].to_owned();
+            rest = &rest[whole.end()..];
+            Reference::Env(name)
+        } else {
+            literal.push(c);
+            rest = &rest[c.len_utf8()..];
+            continue;
+        };
+
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(std::mem::take(&mut literal)));
+        }
+        segments.push(Segment::Reference(reference));
+    }
+
+    if !literal.is_empty() || segments.is_empty() {
+        segments.push(Segment::Literal(literal));
+    }
+
+    Ok(segments)
+}
+
+/// Whether the value holds a reference, so it is only known once interpolated.
+pub fn has_reference(value: &str) -> bool {
+    parse(value).is_ok_and(|segments| segments.iter().any(|x| matches!(x, Segment::Reference(_))))
+}
+
+/// Drops the violations of strings holding `${VAR}` or `$(command)`,
+/// which are only known once the request is interpolated.
+pub fn skip_references(value: &Value, path: &str, violations: &mut Vec<Violation>) {
+    match value {
+        Value::String(s) if has_reference(s) => {
+            violations.retain(|x| x.path != path);
+        }
+        Value::Array(items) => {
+            for (idx, item) in items.iter().enumerate() {
+                skip_references(item, &format!("{}[{}]", path, idx), violations);
+            }
+        }
+        Value::Object(map) => {
+            for (key, item) in map {
+                skip_references(item, &format!("{}.{}", path, key), violations);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Escapes `$` and `{{`, so a value taken from the schema or generated from it,
+/// such as an example, is sent as it is instead of being interpolated.
+pub fn escape(value: &str) -> String {
+    value.replace('$', "\\$").replace("{{", "\\{{")
+}
+
+/// [`escape`] on every string of the value.
+pub fn escape_value(value: &Value) -> Value {
+    map_strings(value, &|x| Value::String(escape(x)))
+}
+
+/// The value as it is sent, for validation: escapes are resolved in the strings
+/// without references, and strings with references are kept as they are.
+pub fn literal(value: &Value) -> Value {
+    map_strings(value, &|x| {
+        let text = parse(x).ok().and_then(|segments| {
+            segments
+                .into_iter()
+                .map(|x| match x {
+                    Segment::Literal(s) => Some(s),
+                    Segment::Reference(_) => None,
+                })
+                .collect::<Option<String>>()
+        });
+        Value::String(text.unwrap_or_else(|| x.to_owned()))
+    })
+}
+
+fn map_strings(value: &Value, f: &impl Fn(&str) -> Value) -> Value {
+    match value {
+        Value::String(s) => f(s),
+        Value::Array(items) => Value::Array(items.iter().map(|x| map_strings(x, f)).collect()),
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(k, v)| (k.clone(), map_strings(v, f)))
+                .collect(),
+        ),
+        _ => value.clone(),
+    }
+}
+
+fn find_closing_paren(s: &str) -> Option<usize> {
+    let mut depth = 0;
+    for (idx, c) in s.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' if depth == 0 => return Some(idx),
+            ')' => depth -= 1,
+            _ => {}
+        }
+    }
+
+    None
+}
+
+/// Resolves the references in the request.
+///
+/// When `keep_vars` is set, the references are replaced with markers instead,
+/// and [`Interpolator::restore`] puts them back in the syntax of the output format.
+pub struct Interpolator {
+    keep_vars: bool,
+    references: Vec<Reference>,
+}
+
+impl Interpolator {
+    pub fn new(keep_vars: bool) -> Self {
+        Self {
+            keep_vars,
+            references: Vec::new(),
+        }
+    }
+
+    fn marker(idx: usize) -> String {
+        format!("__OREQ_REF_{}__", idx)
+    }
+
+    pub fn interpolate_str(&mut self, value: &str) -> Result<String, InterpolateError> {
+        let mut result = String::new();
+        for segment in parse(value)? {
+            match segment {
+                Segment::Literal(s) => result.push_str(&s),
+                Segment::Reference(reference) => result.push_str(&self.reference(reference)?),
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Resolves the reference, or returns its marker when `keep_vars` is set.
+    pub fn reference(&mut self, reference: Reference) -> Result<String, InterpolateError> {
+        if self.keep_vars {
+            let marker = Self::marker(self.references.len());
+            self.references.push(reference);
+            Ok(marker)
+        } else {
+            reference.resolve()
+        }
+    }
+
+    pub fn interpolate_value(&mut self, value: &Value) -> Result<Value, InterpolateError> {
+        Ok(match value {
+            Value::String(s) => Value::String(self.interpolate_str(s)?),
+            Value::Array(items) => Value::Array(
+                items
+                    .iter()
+                    .map(|x| self.interpolate_value(x))
+                    .collect::<Result<_, _>>()?,
+            ),
+            Value::Object(map) => Value::Object(
+                map.iter()
+                    .map(|(k, v)| Ok((k.clone(), self.interpolate_value(v)?)))
+                    .collect::<Result<_, InterpolateError>>()?,
+            ),
+            _ => value.clone(),
+        })
+    }
+
+    pub fn interpolate(&mut self, req: &RequestInit) -> Result<RequestInit, InterpolateError> {
+        Ok(RequestInit {
+            method: req.method.clone(),
+            base: self.interpolate_str(&req.base)?,
+            path: self.interpolate_str(&req.path)?,
+            query: req
+                .query
+                .iter()
+                .map(|(k, v)| {
+                    Ok((
+                        k.clone(),
+                        v.as_ref().map(|v| self.interpolate_value(v)).transpose()?,
+                    ))
+                })
+                .collect::<Result<_, InterpolateError>>()?,
+            header: req
+                .header
+                .iter()
+                .map(|(k, v)| Ok((k.clone(), self.interpolate_value(v)?)))
+                .collect::<Result<_, InterpolateError>>()?,
+            cookie: req
+                .cookie
+                .iter()
+                .map(|(k, v)| Ok((k.clone(), self.interpolate_value(v)?)))
+                .collect::<Result<_, InterpolateError>>()?,
+            body: req
+                .body
+                .as_ref()
+                .map(|x| self.interpolate_value(x))
+                .transpose()?,
+        })
+    }
+
+    /// Replaces the markers in the formatted output with the syntax of `fmt`.
+    pub fn restore(&self, output: String, fmt: impl Fn(&Reference) -> String) -> String {
+        self.references
+            .iter()
+            .enumerate()
+            .fold(output, |acc, (idx, reference)| {
+                acc.replace(&Self::marker(idx), &fmt(reference))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use openapiv3::{OpenAPI, Schema};
+    use oreq::{fake::Faker, schema::skeleton::skeleton};
+
+    use super::*;
+
+    fn literal(s: &str) -> Segment {
+        Segment::Literal(s.to_owned())
+    }
+
+    fn env(name: &str) -> Segment {
+        Segment::Reference(Reference::Env(name.to_owned()))
+    }
+
+    #[test]
+    fn parse_references() {
+        assert_eq!(parse("").unwrap(), [literal("")]);
+        assert_eq!(parse("plain ü").unwrap(), [literal("plain ü")]);
+        assert_eq!(
+            parse("Bearer ${TOKEN}").unwrap(),
+            [literal("Bearer "), env("TOKEN")]
+        );
+        assert_eq!(parse("${ TOKEN }").unwrap(), [env("TOKEN")]);
+        assert_eq!(
+            parse("{{env.A}}-{{ env.B }}").unwrap(),
+            [env("A"), literal("-"), env("B")]
+        );
+        assert_eq!(
+            parse("$(echo $(date) (x))!").unwrap(),
+            [
+                Segment::Reference(Reference::Command("echo $(date) (x)".to_owned())),
+                literal("!")
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_literals() {
+        assert_eq!(parse(r"\${TOKEN}").unwrap(), [literal("${TOKEN}")]);
+        assert_eq!(parse(r"a\$(b)").unwrap(), [literal("a$(b)")]);
+        assert_eq!(parse("$5 and $").unwrap(), [literal("$5 and $")]);
+        assert_eq!(parse("{{ env.1A }}").unwrap(), [literal("{{ env.1A }}")]);
+        assert_eq!(parse("{{ vars.A }}").unwrap(), [literal("{{ vars.A }}")]);
+        assert_eq!(parse("@file").unwrap(), [literal("@file")]);
+    }
+
+    #[test]
+    fn parse_unterminated() {
+        for value in ["${TOKEN", "$(echo", "$(echo (x)", "a ${"] {
+            assert!(
+                matches!(parse(value), Err(InterpolateError::Unterminated(_))),
+                "{value}"
+            );
+        }
+        assert!(!has_reference("${TOKEN"));
+    }
+
+    #[test]
+    fn has_references() {
+        assert!(has_reference("${A}"));
+        assert!(has_reference("x$(date)"));
+        assert!(has_reference("{{ env.A }}"));
+        assert!(!has_reference(r"\${A}"));
+        assert!(!has_reference("a$b"));
+    }
+
+    #[test]
+    fn flag_values() {
+        assert_eq!(flag_value("42").unwrap(), Value::from(42));
+        assert_eq!(flag_value("[1]").unwrap(), serde_json::json!([1]));
+        assert_eq!(flag_value("abc").unwrap(), Value::from("abc"));
+        assert_eq!(flag_value(r"\@abc").unwrap(), Value::from("@abc"));
+        assert_eq!(flag_value("a@b").unwrap(), Value::from("a@b"));
+
+        let path = std::env::temp_dir().join(format!("oreq-flag-{}", std::process::id()));
+        fs::write(&path, "{\"a\": 1}\n").unwrap();
+        let value = flag_value(&format!("@{}", path.display()));
+        fs::remove_file(&path).unwrap();
+        assert_eq!(value.unwrap(), Value::from("{\"a\": 1}"));
+        assert!(matches!(
+            flag_value(&format!("@{}", path.display())),
+            Err(InterpolateError::ReadFile(..))
+        ));
+    }
+
+    #[test]
+    fn interpolate_commands() {
+        let mut interpolator = Interpolator::new(false);
+        assert_eq!(
+            interpolator
+                .interpolate_str("a$(printf 'b\\n\\n')c")
+                .unwrap(),
+            "abc"
+        );
+        assert!(matches!(
+            interpolator.interpolate_str("$(exit 1)"),
+            Err(InterpolateError::Command(_))
+        ));
+        assert!(matches!(
+            interpolator.interpolate_str("${OREQ_TEST_UNSET_VARIABLE}"),
+            Err(InterpolateError::NotPresent(_))
+        ));
+    }
+
+    #[test]
+    fn keep_vars() {
+        let mut interpolator = Interpolator::new(true);
+        let value = serde_json::json!({"a": ["${A}", "$(date)"], "b": 1});
+        let value = interpolator.interpolate_value(&value).unwrap();
+        let output = interpolator.restore(value.to_string(), |x| match x {
+            Reference::Env(name) => format!("${}", name),
+            Reference::Command(command) => format!("`{}`", command),
+            Reference::File(path) => path.display().to_string(),
+        });
+        assert_eq!(output, r#"{"a":["$A","`date`"],"b":1}"#);
+    }
+
+    #[test]
+    fn parse_escaped_templates() {
+        assert_eq!(parse(r"\{{ env.A }}").unwrap(), [literal("{{ env.A }}")]);
+        assert_eq!(parse(r"\\{{ env.A }}").unwrap(), [literal(r"\{{ env.A }}")]);
+    }
+
+    #[test]
+    fn escaped_values_are_literal() {
+        let marker = std::env::temp_dir().join(format!("oreq-escape-{}", std::process::id()));
+        let value = serde_json::json!({
+            "command": format!("$(touch {})", marker.display()),
+            "env": ["${HOME}", "{{ env.HOME }}", "{{{ env.HOME }}}"],
+            "escapes": [r"\$5", r"a\", r"\{{"],
+            "count": 1
+        });
+        let escaped = escape_value(&value);
+
+        assert_eq!(super::literal(&escaped), value);
+        assert!(!has_reference(&escape(r"$(date) ${A} \${B}")));
+        let mut interpolator = Interpolator::new(false);
+        assert_eq!(interpolator.interpolate_value(&escaped).unwrap(), value);
+        assert!(!marker.exists());
+    }
+
+    #[test]
+    fn schema_examples_are_not_executed() {
+        let marker = std::env::temp_dir().join(format!("oreq-example-{}", std::process::id()));
+        let command = format!("$(touch {})", marker.display());
+        let api: OpenAPI = serde_json::from_value(serde_json::json!({
+            "openapi": "3.0.0",
+            "info": {"title": "", "version": ""},
+            "paths": {}
+        }))
+        .unwrap();
+        let schema: Schema = serde_json::from_value(serde_json::json!({
+            "type": "object",
+            "required": ["name", "kind", "code"],
+            "properties": {
+                "name": {"type": "string", "example": command},
+                "kind": {"type": "string", "enum": ["${HOME}"]},
+                "code": {"type": "string", "pattern": "^\\$\\(x\\)$"}
+            }
+        }))
+        .unwrap();
+
+        let skeleton = skeleton(&api, &schema, None, Some(escape)).unwrap();
+        let mut faker = Faker::new(Some(1));
+        faker.with_escape(Some(escape));
+        let fake = faker.value(&api, &schema, None).unwrap();
+
+        let mut interpolator = Interpolator::new(false);
+        let skeleton = interpolator.interpolate_value(&skeleton).unwrap();
+        let fake = interpolator.interpolate_value(&fake).unwrap();
+        assert_eq!(skeleton["name"], command.as_str());
+        assert_eq!(skeleton["kind"], "${HOME}");
+        assert_eq!(fake["kind"], "${HOME}");
+        assert_eq!(fake["code"], "$(x)");
+        assert!(!marker.exists());
+    }
+}
@@ -1,2 +1,4 @@
+pub mod fake;
 pub mod prompts;
 pub mod schema;
+pub mod validate;
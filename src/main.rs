@@ -2,9 +2,18 @@ use std::process::exit;
 
 use clap::Parser;
 
+mod answers;
+mod cases;
 mod cli;
+mod collection;
+mod config;
+mod describe;
+mod editor;
 mod error;
+mod field;
 mod fmt;
+mod history;
+mod interpolate;
 mod prompt;
 mod req;
 
@@ -1,18 +1,30 @@
 use anyhow::anyhow;
 use http::Method;
 use indexmap::IndexMap;
-use openapiv3::{OpenAPI, Operation, Parameter, ParameterData, ParameterSchemaOrContent, PathItem};
+use openapiv3::{
+    OpenAPI, Parameter, ParameterData, ParameterSchemaOrContent, Schema, SchemaKind, Type,
+};
 use promptuity::{prompts::SelectOption, Promptuity, Terminal, Theme};
 
 use oreq::{
-    prompts::{enumeration::Enumeration, optional_prompt_builder, prompt_builder},
-    schema::{error::SchemaError, reference::ReferenceOrExt},
+    fake::Faker,
+    prompts::{
+        editable::{Edit, Editable},
+        enumeration::Enumeration,
+        is_answered, is_nested,
+        navigable::Navigable,
+        optional_prompt_builder, prompt_builder, PromptConfig,
+    },
+    schema::{error::SchemaError, operation::OperationItem, pattern, reference::ReferenceOrExt},
+    validate,
 };
 use serde_json::Value;
 
 use crate::{
+    answers::{Answered, Answers},
+    editor::{Editor, EditorFormat},
     error::AppError,
-    req::{ParamsValue, RequestInit},
+    interpolate,
 };
 
 struct ParamsMap<T> {
@@ -51,6 +63,12 @@ where
 {
     api: OpenAPI,
     provider: Promptuity<'a, W>,
+    interactive: bool,
+    repeated: Vec<(&'static str, String)>,
+    config: PromptConfig,
+    review: bool,
+    editor: Option<EditorFormat>,
+    fake: Option<Faker>,
 }
 
 impl<'a, W> Prompt<'a, W>
@@ -61,77 +79,99 @@ where
         Self {
             api,
             provider: Promptuity::new(term, theme),
+            interactive: true,
+            repeated: Vec::new(),
+            config: PromptConfig::default(),
+            review: true,
+            editor: None,
+            fake: None,
         }
     }
 
+    /// When disabled, missing values are reported as an error instead of prompting.
+    pub fn with_interactive(&mut self, interactive: bool) -> &mut Self {
+        self.interactive = interactive;
+        self
+    }
+
+    /// When enabled, every answer is listed to be edited or confirmed before the output.
+    pub fn with_review(&mut self, review: bool) -> &mut Self {
+        self.review = review;
+        self
+    }
+
+    /// When set, the request body is edited in `$EDITOR` instead of being prompted.
+    /// `<C-e>` at the body opens it in any case.
+    pub fn with_editor(&mut self, editor: Option<EditorFormat>) -> &mut Self {
+        self.editor = editor;
+        self
+    }
+
+    /// When set, the values which are not given are filled with fake data instead of being prompted.
+    pub fn with_fake(&mut self, fake: Option<Faker>) -> &mut Self {
+        self.fake = fake;
+        self
+    }
+
+    pub fn with_config(&mut self, config: PromptConfig) -> &mut Self {
+        self.config = config;
+        self
+    }
+
+    /// Parameters given more than once, by location. Only array parameters accept them.
+    pub fn with_repeated(&mut self, repeated: Vec<(&'static str, String)>) -> &mut Self {
+        self.repeated = repeated;
+        self
+    }
+
+    /// Prompts the values which are not given by `input`.
+    /// `defaults` are pre-filled in the prompts.
     pub fn run(
         &mut self,
         path: Option<String>,
         method: Option<Method>,
-        path_params: IndexMap<String, Value>,
-        query_params: IndexMap<String, Value>,
-        header: IndexMap<String, Value>,
-        fields: IndexMap<String, Value>,
-    ) -> Result<RequestInit, AppError> {
-        self.provider.term().clear()?;
-
-        self.provider.with_intro("Build Request").begin()?;
-        let mut path_prompt = self.path_prompt()?;
-        let (path, path_item) = if let Some(path) = path {
-            let path_item = self
-                .api
-                .paths
-                .paths
-                .get(&path)
-                .ok_or_else(|| anyhow!("Path not found"))?;
-            let path_item = path_item.item(&self.api)?;
-            (path, path_item.clone())
-        } else {
-            self.provider.prompt(&mut path_prompt)?
-        };
-        let mut method_prompt = self.method_prompt(&path_item)?;
-        let (method, operation) = if let Some(method) = method {
-            match method {
-                Method::GET => {
-                    let operation = path_item
-                        .get
-                        .clone()
-                        .ok_or_else(|| anyhow!("Method not found"))?;
-                    ("GET".to_owned(), operation)
-                }
-                Method::POST => {
-                    let operation = path_item
-                        .post
-                        .clone()
-                        .ok_or_else(|| anyhow!("Method not found"))?;
-                    ("POST".to_owned(), operation)
-                }
-                Method::PUT => {
-                    let operation = path_item
-                        .put
-                        .clone()
-                        .ok_or_else(|| anyhow!("Method not found"))?;
-                    ("PUT".to_owned(), operation)
-                }
-                Method::DELETE => {
-                    let operation = path_item
-                        .delete
-                        .clone()
-                        .ok_or_else(|| anyhow!("Method not found"))?;
-                    ("DELETE".to_owned(), operation)
-                }
-                Method::PATCH => {
-                    let operation = path_item
-                        .patch
-                        .clone()
-                        .ok_or_else(|| anyhow!("Method not found"))?;
-                    ("PATCH".to_owned(), operation)
+        input: Answers,
+        defaults: Answers,
+    ) -> Result<Answered, AppError> {
+        if self.interactive {
+            self.provider.term().clear()?;
+            self.provider.with_intro("Build Request").begin()?;
+        }
+
+        let item = match (&path, &method) {
+            (Some(path), Some(method)) => OperationItem::find(&self.api, method.as_str(), path)?
+                .ok_or_else(|| anyhow!("Operation not found: {} {}", method, path))?,
+            _ => {
+                let mut items = OperationItem::all(&self.api)?
+                    .into_iter()
+                    .filter(|x| !(self.config.hide_deprecated && x.operation.deprecated))
+                    .filter(|x| path.as_ref().is_none_or(|path| &x.path == path))
+                    .filter(|x| method.as_ref().is_none_or(|m| x.method == m.as_str()))
+                    .collect::<Vec<_>>();
+                match items.len() {
+                    0 => return Err(anyhow!("Path not found: {}", path.unwrap_or_default()).into()),
+                    1 if path.is_some() => items.remove(0),
+                    _ if !self.interactive => {
+                        return Err(AppError::Incomplete(
+                            [
+                                (path.is_none(), "operation (--operation or --path)"),
+                                (method.is_none(), "method (-X)"),
+                            ]
+                            .into_iter()
+                            .filter(|(missing, _)| *missing)
+                            .map(|(_, x)| x.to_owned())
+                            .collect(),
+                        ))
+                    }
+                    _ => self.provider.prompt(&mut self.operation_prompt(items))?,
                 }
-                _ => self.provider.prompt(&mut method_prompt)?,
             }
-        } else {
-            self.provider.prompt(&mut method_prompt)?
         };
+        let mut input = input;
+        if let Some(faker) = &mut self.fake {
+            input.fake(&self.api, &item, faker)?;
+        }
+        let (method, path, operation) = (item.method, item.path, item.operation);
 
         let mut params_data = ParamsMap::default();
         for param in operation.parameters {
@@ -153,165 +193,621 @@ where
             }
         }
 
-        let mut params = ParamsMap::default();
-        let prompts = vec![
-            (
-                &mut params.path,
-                &params_data.path,
-                path_params,
-                "Path Parameters",
-            ),
-            (
-                &mut params.query,
-                &params_data.query,
-                query_params,
-                "Query Parameters",
-            ),
-            (
-                &mut params.header,
-                &params_data.header,
-                header,
-                "Header Parameters",
-            ),
-            (
-                &mut params.cookie,
-                &params_data.cookie,
-                IndexMap::new(),
-                "Cookie Parameters",
-            ),
-        ];
+        let request_body = match &operation.request_body {
+            Some(req_body) => {
+                let req_body = req_body.item(&self.api)?;
+                let content = req_body
+                    .content
+                    .get("application/json")
+                    .filter(|x| x.schema.is_some())
+                    .ok_or_else(|| anyhow!("Only supported 'application/json'"))?;
+                let schema = content.schema.as_ref().unwrap().item(&self.api)?.clone();
+                let mut examples = Vec::new();
+                for (name, example) in &content.examples {
+                    let example = example.item(&self.api)?;
+                    if let Some(value) = &example.value {
+                        examples.push((
+                            name.clone(),
+                            example.summary.clone(),
+                            interpolate::escape_value(value),
+                        ));
+                    }
+                }
+                Some((req_body.required, schema, examples))
+            }
+            None => None,
+        };
 
-        for (map, data, cli_input, msg) in prompts {
-            if data.is_empty() {
-                continue;
+        // A pattern which does not compile would reject every answer, so it is reported first.
+        // One the `regex` crate does not support only leaves its field unchecked.
+        let mut warnings = Vec::new();
+        for param in params_data
+            .path
+            .iter()
+            .chain(&params_data.query)
+            .chain(&params_data.header)
+            .chain(&params_data.cookie)
+        {
+            warnings.extend(pattern::check_parameter(
+                &self.api,
+                &param.format,
+                &param.name,
+            )?);
+        }
+        if let Some((_, schema, _)) = &request_body {
+            warnings.extend(pattern::check(&self.api, schema, "Request Body")?);
+        }
+        for warning in warnings {
+            eprintln!("Warning: {}", warning);
+        }
+
+        // Values given up front are checked before prompting anything, so a typo
+        // does not cost the answers typed afterwards.
+        let mut problems = Vec::new();
+        for (location, values, data) in [
+            ("path", &mut input.path, &params_data.path),
+            ("query", &mut input.query, &params_data.query),
+            ("header", &mut input.header, &params_data.header),
+            ("cookie", &mut input.cookie, &params_data.cookie),
+        ] {
+            for param in data {
+                let repeated = self
+                    .repeated
+                    .iter()
+                    .any(|(l, x)| *l == location && x == &param.name);
+                if repeated && !self.is_array_parameter(param)? {
+                    problems.push(format!(
+                        "{}: Value is given more than once, but the parameter is not an array (type)",
+                        param.name
+                    ));
+                    // The value is kept, so it is not reported as missing too.
+                    continue;
+                }
+                if let Some(value) = values.shift_remove(&param.name) {
+                    let value = self.validate_parameter(param, value, &mut problems)?;
+                    values.insert(param.name.clone(), value);
+                }
+            }
+        }
+        if let (Some((_, schema, _)), Some(body)) = (&request_body, input.body.take()) {
+            let body = validate::coerce(&self.api, schema, body)?;
+            let literal = interpolate::literal(&body);
+            let mut violations = if self.interactive {
+                validate::validate_partial(&self.api, schema, &literal, "Request Body")?
+            } else {
+                validate::validate(&self.api, schema, &literal, "Request Body")?
+            };
+            interpolate::skip_references(&body, "Request Body", &mut violations);
+            if !self.config.include_read_only {
+                violations.extend(validate::read_only(
+                    &self.api,
+                    schema,
+                    &body,
+                    "Request Body",
+                )?);
             }
+            problems.extend(violations.into_iter().map(|x| x.to_string()));
+            input.body = Some(body);
+        }
+        if self.interactive && !problems.is_empty() {
+            return Err(AppError::InvalidInput(problems));
+        }
 
-            self.provider.step(msg)?;
+        let mut answers = Answers::default();
+        let mut fields = Vec::new();
+        for (location, data, step) in [
+            ("path", &params_data.path, "Path Parameters"),
+            ("query", &params_data.query, "Query Parameters"),
+            ("header", &params_data.header, "Header Parameters"),
+            ("cookie", &params_data.cookie, "Cookie Parameters"),
+        ] {
             for param in data {
-                let value = if param.required {
-                    let mut prompt = self.parameter_prompt(param)?;
-                    if let Some(value) = cli_input.get(&param.name) {
-                        Some(value.clone())
-                    } else {
-                        Some(self.provider.prompt(&mut *prompt)?)
+                if let Some(value) = input.params(location).get(&param.name) {
+                    answers
+                        .params_mut(location)
+                        .insert(param.name.clone(), value.clone());
+                } else if !param.required
+                    && (self.config.required_only
+                        || self.fake.is_some()
+                        || (self.config.hide_deprecated && param.deprecated == Some(true)))
+                {
+                    continue;
+                } else if !self.interactive {
+                    if param.required {
+                        problems.push(format!("{}: '{}' is required", step, param.name));
                     }
                 } else {
-                    let mut prompt = self.optional_parameter_prompt(param)?;
-                    if let Some(value) = cli_input.get(&param.name) {
-                        Some(value.clone())
-                    } else {
-                        self.provider.prompt(&mut *prompt)?
+                    fields.push(Field::Parameter {
+                        location,
+                        step,
+                        parameter: param,
+                    });
+                }
+            }
+        }
+
+        if let Some((required, req_body, _)) = &request_body {
+            // Optional values left out of fake data are skipped rather than prompted.
+            let answered = match &input.body {
+                Some(_) if self.fake.is_some() => true,
+                Some(body) => is_answered(&self.api, req_body, body, &self.config)?,
+                None => self.fake.is_some() && !*required,
+            };
+            if !self.interactive {
+                if input.body.is_none() && *required {
+                    problems.push("Request Body is required".to_owned());
+                }
+                answers.body = input.body.clone();
+            } else if answered {
+                answers.body = input.body.clone();
+            } else {
+                fields.push(Field::Body);
+            }
+        }
+
+        // `<S-Tab>` on a field goes back to the previous one, pre-filled with its answer.
+        let mut idx = 0;
+        let mut step = None;
+        while idx < fields.len() {
+            let field = &fields[idx];
+            let answer = match field {
+                Field::Parameter {
+                    location,
+                    step: name,
+                    parameter,
+                } => {
+                    if step != Some(*name) {
+                        self.provider.step(*name)?;
+                        step = Some(*name);
                     }
-                };
+                    let default = answers
+                        .params(location)
+                        .get(&parameter.name)
+                        .or_else(|| defaults.params(location).get(&parameter.name))
+                        .cloned();
+                    self.prompt_parameter(parameter, default)?
+                }
+                Field::Body => {
+                    let (_, req_body, examples) = request_body.as_ref().unwrap();
+                    self.prompt_body(req_body, examples, &input, &answers, &defaults)?
+                        .map(Some)
+                }
+            };
 
-                map.push((param.name.to_owned(), value));
+            match (field, answer) {
+                (_, None) => idx = idx.saturating_sub(1),
+                (
+                    Field::Parameter {
+                        location,
+                        parameter,
+                        ..
+                    },
+                    Some(value),
+                ) => {
+                    let map = answers.params_mut(location);
+                    match value {
+                        Some(value) => map.insert(parameter.name.clone(), value),
+                        None => map.shift_remove(&parameter.name),
+                    };
+                    idx += 1;
+                }
+                (Field::Body, Some(value)) => {
+                    answers.body = value;
+                    idx += 1;
+                }
             }
         }
+        for (location, data) in [
+            ("path", &params_data.path),
+            ("query", &params_data.query),
+            ("header", &params_data.header),
+            ("cookie", &params_data.cookie),
+        ] {
+            answers
+                .params_mut(location)
+                .sort_by_cached_key(|name, _| data.iter().position(|x| &x.name == name));
+        }
 
-        let req_body = if let Some(req_body) = operation.request_body {
-            let req_body = req_body.item(&self.api)?;
-            let req_body = req_body
-                .content
-                .get("application/json")
-                .and_then(|x| x.schema.to_owned())
-                .ok_or_else(|| anyhow!("Only supported 'application/json'"))?;
-            let req_body = req_body.item(&self.api)?;
+        if !problems.is_empty() {
+            return Err(AppError::Incomplete(problems));
+        }
 
-            let mut prompt = prompt_builder(
-                &self.api,
-                req_body,
-                "Request Body".to_owned(),
-                req_body.schema_data.description.clone(),
-                Some(fields),
-            );
-            let value = self.provider.prompt(&mut *prompt)?;
-            Some(value)
-        } else {
-            None
-        };
+        if self.interactive && self.review {
+            let title = format!("Review {} {}", method, path);
+            let body = request_body.as_ref().map(|(_, schema, _)| schema);
+            self.review(title, &mut answers, &params_data, body)?;
+        }
 
-        self.provider.finish()?;
+        if self.interactive {
+            self.provider.finish()?;
+        }
 
-        Ok(RequestInit {
+        Ok(Answered {
             method,
-            base: String::new(),
-            path: params
-                .path
-                .into_iter()
-                .filter_map(|(k, v)| v.map(|v| (k.to_owned(), ParamsValue::from(v))))
-                .fold(path, |acc, (name, value)| {
-                    acc.replace(&format!("{{{}}}", name), &value.to_string())
-                }),
-            query: params.query,
-            header: params
-                .header
-                .into_iter()
-                .filter_map(|(k, v)| v.map(|v| (k.to_owned(), v)))
-                .collect(),
-            cookie: params
-                .cookie
-                .into_iter()
-                .filter_map(|(k, v)| v.map(|v| (k.to_owned(), v)))
-                .collect(),
-            body: req_body,
+            path,
+            operation_id: operation.operation_id,
+            answers,
         })
     }
 
-    fn path_prompt(&self) -> Result<Enumeration<(String, PathItem)>, SchemaError> {
-        let mut paths = IndexMap::new();
+    fn operation_prompt(&self, mut items: Vec<OperationItem>) -> Enumeration<OperationItem> {
+        OperationItem::group_by_tag(&self.api, &mut items);
+        items.sort_by_key(|x| x.operation.deprecated);
 
-        for (path, path_item) in self.api.paths.clone() {
-            let item = path_item.item(&self.api)?;
-            paths.insert(path, item.clone());
-        }
-
-        let options = paths
+        let keywords = items
+            .iter()
+            .map(|x| {
+                let operation = &x.operation;
+                [
+                    operation.operation_id.clone(),
+                    operation.description.clone(),
+                ]
+                .into_iter()
+                .flatten()
+                .chain(operation.tags.clone())
+                .collect::<Vec<_>>()
+                .join(" ")
+            })
+            .collect();
+        let options = items
             .into_iter()
-            .map(|(path, item)| {
-                let opts = SelectOption::new(path.to_owned(), (path.to_owned(), item.clone()));
-                if let Some(description) = item.description {
-                    opts.with_hint(description)
+            .map(|x| {
+                let hint = [
+                    x.operation.tags.first().map(|x| format!("#{}", x)),
+                    x.operation.operation_id.clone(),
+                ]
+                .into_iter()
+                .flatten()
+                .collect::<Vec<_>>()
+                .join(" ");
+                let hint = if x.operation.deprecated {
+                    format!("deprecated {}", hint).trim_end().to_owned()
+                } else {
+                    hint
+                };
+                let option = SelectOption::new(x.label(), x);
+                if hint.is_empty() {
+                    option
                 } else {
-                    opts
+                    option.with_hint(hint)
                 }
             })
             .collect();
 
-        Ok(Enumeration::new("Path".to_owned(), options))
+        let mut prompt = Enumeration::new("Operation".to_owned(), options);
+        prompt.with_keywords(keywords);
+        prompt
+    }
+
+    /// Returns `None` to go back, and `Some(None)` when an optional parameter is skipped.
+    fn prompt_parameter(
+        &mut self,
+        parameter: &ParameterData,
+        default: Option<Value>,
+    ) -> Result<Option<Option<Value>>, AppError> {
+        let nested = match &parameter.format {
+            ParameterSchemaOrContent::Schema(schema) => is_nested(schema.item(&self.api)?),
+            ParameterSchemaOrContent::Content(_) => false,
+        };
+        if parameter.required {
+            let prompt = self.parameter_prompt(parameter, default)?;
+            let mut prompt = Navigable::from_boxed(prompt, &self.config);
+            prompt.with_nested(nested);
+            Ok(self.provider.prompt(&mut prompt)?.map(Some))
+        } else {
+            let prompt = self.optional_parameter_prompt(parameter, default)?;
+            let mut prompt = Navigable::from_boxed(prompt, &self.config);
+            prompt.with_nested(nested);
+            Ok(self.provider.prompt(&mut prompt)?)
+        }
     }
 
-    fn method_prompt(
+    /// Returns `None` to go back.
+    fn prompt_body(
+        &mut self,
+        schema: &Schema,
+        examples: &[(String, Option<String>, Value)],
+        input: &Answers,
+        answers: &Answers,
+        defaults: &Answers,
+    ) -> Result<Option<Value>, AppError> {
+        // Coming back to the body edits the previous answer instead of completing the input.
+        let (value, default) = match &answers.body {
+            Some(previous) => (None, Some(previous.clone())),
+            None => {
+                // A named example is pre-filled like a previous answer, to be edited.
+                let default = match &defaults.body {
+                    None if !examples.is_empty() => self
+                        .provider
+                        .prompt(&mut example_prompt(examples.to_vec()))?,
+                    default => default.clone(),
+                };
+                (input.body.clone(), default)
+            }
+        };
+
+        let (mut value, mut default) = (value, default);
+        if self.editor.is_some() {
+            if let Some(body) = self.edit_body(schema, value.as_ref().or(default.as_ref()))? {
+                return Ok(Some(body));
+            }
+        }
+
+        loop {
+            let prompt = prompt_builder(
+                &self.api,
+                schema,
+                "Request Body".to_owned(),
+                schema.schema_data.description.clone(),
+                value.clone(),
+                default.clone(),
+                &self.config,
+            );
+            let mut prompt = Editable::from_boxed(prompt);
+            prompt.with_nested(is_nested(schema));
+            let mut prompt = Navigable::from_boxed(Box::new(prompt), &self.config);
+            prompt.with_nested(is_nested(schema));
+            match self.provider.prompt(&mut prompt)? {
+                None => return Ok(None),
+                Some(Edit::Done(body)) => return Ok(Some(body)),
+                // The fields answered so far are carried to the editor, and back to the prompts.
+                Some(Edit::Editor(partial)) => {
+                    let seed = partial.or(value.take()).or(default.clone());
+                    if let Some(body) = self.edit_body(schema, seed.as_ref())? {
+                        return Ok(Some(body));
+                    }
+                    default = seed;
+                }
+            }
+        }
+    }
+
+    /// Edits the body in `$EDITOR`. `None` when the file is emptied, to prompt the fields instead.
+    fn edit_body(
+        &mut self,
+        schema: &Schema,
+        seed: Option<&Value>,
+    ) -> Result<Option<Value>, AppError> {
+        let mut editor = Editor::new(&self.api, schema, self.editor.unwrap_or_default());
+        editor.with_include_read_only(self.config.include_read_only);
+
+        let term = self.provider.term();
+        term.disable_raw()?;
+        term.cursor_show()?;
+        let body = editor.edit(seed);
+        let term = self.provider.term();
+        term.enable_raw()?;
+        term.cursor_hide()?;
+
+        let body = body?;
+        if let Some(body) = &body {
+            self.provider.step("Request Body")?;
+            self.provider.log(preview(Some(body)))?;
+        }
+        Ok(body)
+    }
+
+    /// Lists every answer until "Send" is selected. The selected answer is prompted
+    /// again, where skipping keeps a required value and removes an optional one.
+    fn review(
+        &mut self,
+        title: String,
+        answers: &mut Answers,
+        params: &ParamsMap<ParameterData>,
+        body: Option<&Schema>,
+    ) -> Result<(), AppError> {
+        loop {
+            let mut options =
+                vec![SelectOption::new("Send".to_owned(), ReviewItem::Send).with_hint("confirm")];
+            for (location, data) in [
+                ("path", &params.path),
+                ("query", &params.query),
+                ("header", &params.header),
+                ("cookie", &params.cookie),
+            ] {
+                for param in data {
+                    let value = answers.params(location).get(&param.name);
+                    options.push(SelectOption::new(
+                        format!("{} {}: {}", location, param.name, preview(value)),
+                        ReviewItem::Parameter(location, param.name.clone()),
+                    ));
+                }
+            }
+            if let Some(schema) = body {
+                match (&schema.schema_kind, &answers.body) {
+                    (SchemaKind::Type(Type::Object(object)), Some(Value::Object(map)))
+                        if !object.properties.is_empty() =>
+                    {
+                        for key in object.properties.keys() {
+                            options.push(SelectOption::new(
+                                format!("body.{}: {}", key, preview(map.get(key))),
+                                ReviewItem::Body(Some(key.clone())),
+                            ));
+                        }
+                    }
+                    (_, value) => options.push(SelectOption::new(
+                        format!("body: {}", preview(value.as_ref())),
+                        ReviewItem::Body(None),
+                    )),
+                }
+                options.push(
+                    SelectOption::new("body in $EDITOR".to_owned(), ReviewItem::Editor)
+                        .with_hint("edit the whole body"),
+                );
+            }
+
+            let item = self
+                .provider
+                .prompt(&mut Enumeration::new(title.clone(), options))?;
+            match item {
+                ReviewItem::Send => return Ok(()),
+                ReviewItem::Parameter(location, name) => {
+                    let Some(param) = [
+                        ("path", &params.path),
+                        ("query", &params.query),
+                        ("header", &params.header),
+                        ("cookie", &params.cookie),
+                    ]
+                    .into_iter()
+                    .filter(|(x, _)| *x == location)
+                    .find_map(|(_, data)| data.iter().find(|x| x.name == name)) else {
+                        continue;
+                    };
+                    let current = answers.params(location).get(&name).cloned();
+                    let mut prompt = self.optional_parameter_prompt(param, current)?;
+                    match self.provider.prompt(&mut *prompt)? {
+                        Some(value) => {
+                            answers.params_mut(location).insert(name, value);
+                        }
+                        None if !param.required => {
+                            answers.params_mut(location).shift_remove(&name);
+                        }
+                        None => {}
+                    }
+                }
+                ReviewItem::Body(key) => {
+                    let Some(schema) = body else {
+                        continue;
+                    };
+                    self.review_body(schema, key, answers)?;
+                }
+                ReviewItem::Editor => {
+                    let Some(schema) = body else {
+                        continue;
+                    };
+                    if let Some(value) = self.edit_body(schema, answers.body.as_ref())? {
+                        answers.body = Some(value);
+                    }
+                }
+            }
+        }
+    }
+
+    fn review_body(
+        &mut self,
+        schema: &Schema,
+        key: Option<String>,
+        answers: &mut Answers,
+    ) -> Result<(), AppError> {
+        let Some(key) = key else {
+            let mut prompt = optional_prompt_builder(
+                &self.api,
+                schema,
+                "Request Body".to_owned(),
+                schema.schema_data.description.clone(),
+                None,
+                answers.body.clone(),
+                &self.config,
+            );
+            if let Some(value) = self.provider.prompt(&mut *prompt)? {
+                answers.body = Some(value);
+            }
+            return Ok(());
+        };
+
+        let SchemaKind::Type(Type::Object(object)) = &schema.schema_kind else {
+            return Ok(());
+        };
+        let Some(property) = object.properties.get(&key) else {
+            return Ok(());
+        };
+        let property = property.clone().unbox();
+        let property = property.item(&self.api)?;
+        let Some(Value::Object(map)) = &mut answers.body else {
+            return Ok(());
+        };
+
+        let mut prompt = optional_prompt_builder(
+            &self.api,
+            property,
+            format!("body.{}", key),
+            property.schema_data.description.clone(),
+            None,
+            map.get(&key).cloned(),
+            &self.config,
+        );
+        match self.provider.prompt(&mut *prompt)? {
+            Some(value) => {
+                map.insert(key, value);
+            }
+            None if !object.required.contains(&key) => {
+                map.remove(&key);
+            }
+            None => {}
+        }
+        Ok(())
+    }
+
+    fn is_array_parameter(&self, parameter: &ParameterData) -> Result<bool, SchemaError> {
+        Ok(match &parameter.format {
+            ParameterSchemaOrContent::Schema(schema) => matches!(
+                schema.item(&self.api)?.schema_kind,
+                SchemaKind::Type(Type::Array(_))
+            ),
+            ParameterSchemaOrContent::Content(_) => false,
+        })
+    }
+
+    fn validate_parameter(
+        &self,
+        parameter: &ParameterData,
+        value: Value,
+        problems: &mut Vec<String>,
+    ) -> Result<Value, SchemaError> {
+        match &parameter.format {
+            ParameterSchemaOrContent::Schema(schema) => {
+                let schema = schema.item(&self.api)?;
+                let value = validate::coerce(&self.api, schema, value)?;
+                let mut violations = validate::validate(
+                    &self.api,
+                    schema,
+                    &interpolate::literal(&value),
+                    &parameter.name,
+                )?;
+                interpolate::skip_references(&value, &parameter.name, &mut violations);
+                problems.extend(violations.into_iter().map(|x| x.to_string()));
+                Ok(value)
+            }
+            ParameterSchemaOrContent::Content(_) => Ok(value),
+        }
+    }
+
+    /// The schema of the parameter, with the example of the parameter shown instead of its own.
+    fn parameter_schema(
         &self,
-        path_item: &PathItem,
-    ) -> Result<Enumeration<(String, Operation)>, SchemaError> {
-        let options = vec![
-            ("GET", path_item.get.clone()),
-            ("POST", path_item.post.clone()),
-            ("PUT", path_item.put.clone()),
-            ("DELETE", path_item.delete.clone()),
-            ("PATCH", path_item.patch.clone()),
-        ]
-        .into_iter()
-        .filter_map(|(k, x)| x.map(|v| (k, v)))
-        .map(|(k, v)| SelectOption::new(k.to_owned(), (k.to_owned(), v.clone())))
-        .collect::<Vec<_>>();
-
-        Ok(Enumeration::new("Method".to_owned(), options))
+        parameter: &ParameterData,
+        schema: &Schema,
+    ) -> Result<Schema, SchemaError> {
+        let mut schema = schema.clone();
+        let example = match &parameter.example {
+            Some(example) => Some(example.clone()),
+            None => match parameter.examples.values().next() {
+                Some(example) => example.item(&self.api)?.value.clone(),
+                None => None,
+            },
+        };
+        if example.is_some() {
+            schema.schema_data.example = example;
+        }
+        Ok(schema)
     }
 
     fn parameter_prompt(
         &self,
         parameter: &ParameterData,
+        default: Option<Value>,
     ) -> Result<Box<dyn promptuity::Prompt<Output = Value>>, SchemaError> {
         match parameter.format.clone() {
             ParameterSchemaOrContent::Schema(schema) => {
-                let item = schema.item(&self.api)?;
+                let item = self.parameter_schema(parameter, schema.item(&self.api)?)?;
                 Ok(prompt_builder(
                     &self.api,
-                    item,
-                    parameter.name.clone(),
+                    &item,
+                    label(parameter),
                     parameter.description.clone(),
                     None,
+                    default,
+                    &self.config,
                 ))
             }
             ParameterSchemaOrContent::Content(_) => Err(SchemaError::UnsupportedSchema),
@@ -321,19 +817,79 @@ where
     fn optional_parameter_prompt(
         &self,
         parameter: &ParameterData,
+        default: Option<Value>,
     ) -> Result<Box<dyn promptuity::Prompt<Output = Option<Value>>>, SchemaError> {
         match parameter.format.clone() {
             ParameterSchemaOrContent::Schema(schema) => {
-                let item = schema.item(&self.api)?;
+                let item = self.parameter_schema(parameter, schema.item(&self.api)?)?;
                 Ok(optional_prompt_builder(
                     &self.api,
-                    item,
-                    parameter.name.clone(),
+                    &item,
+                    label(parameter),
                     parameter.description.clone(),
                     None,
+                    default,
+                    &self.config,
                 ))
             }
             ParameterSchemaOrContent::Content(_) => Err(SchemaError::UnsupportedSchema),
         }
     }
 }
+
+/// A value prompted by [`Prompt::run`], in order.
+enum Field<'a> {
+    Parameter {
+        location: &'static str,
+        step: &'static str,
+        parameter: &'a ParameterData,
+    },
+    Body,
+}
+
+#[derive(Debug, Clone, Default)]
+enum ReviewItem {
+    #[default]
+    Send,
+    Parameter(&'static str, String),
+    /// A property of the body, or the whole body.
+    Body(Option<String>),
+    Editor,
+}
+
+/// The value shortened to a line of the review.
+fn preview(value: Option<&Value>) -> String {
+    let Some(value) = value else {
+        return "-".to_owned();
+    };
+    let text = value.to_string();
+    if text.chars().count() > 60 {
+        format!("{}…", text.chars().take(59).collect::<String>())
+    } else {
+        text
+    }
+}
+
+/// Asks which named example of the media type the body starts from.
+fn example_prompt(examples: Vec<(String, Option<String>, Value)>) -> Enumeration<Option<Value>> {
+    let options = std::iter::once(SelectOption::new("Empty".to_owned(), None))
+        .chain(examples.into_iter().map(|(name, summary, value)| {
+            let option = SelectOption::new(name, Some(value));
+            match summary {
+                Some(summary) => option.with_hint(summary),
+                None => option,
+            }
+        }))
+        .collect();
+
+    Enumeration::new("Start from".to_owned(), options)
+}
+
+/// The parameter name shown in the prompts.
+fn label(parameter: &ParameterData) -> String {
+    if parameter.deprecated == Some(true) {
+        format!("{} (deprecated)", parameter.name)
+    } else {
+        parameter.name.clone()
+    }
+}
@@ -1,19 +1,32 @@
+use std::collections::VecDeque;
+
+use crossterm::style::Color;
 use openapiv3::{ArrayType, OpenAPI, ReferenceOr, Schema};
 use promptuity::{
     event::{KeyCode, KeyModifiers},
+    style::Styled,
     Prompt, PromptBody, PromptState, RenderPayload, Validator,
 };
 use serde_json::{json, Value};
 
 use super::{
-    prompt_builder,
+    is_answered, is_nested, prompt_builder,
     utils::{fmt_body, fmt_input},
+    PromptConfig,
 };
-use crate::schema::reference::ReferenceOrExt;
+use crate::{schema::reference::ReferenceOrExt, validate};
 
 pub trait ArrayFormatter {
     fn fmt_prompt(&self, submitted: String, input: String) -> String;
-    fn fmt_submitted_item(&self, idx: usize, value: Value) -> String;
+    /// `error` is the first violation of the item, if any.
+    fn fmt_submitted_item(&self, idx: usize, value: Value, error: Option<String>) -> String;
+    fn fmt_list_item(
+        &self,
+        idx: usize,
+        value: Value,
+        error: Option<String>,
+        selected: bool,
+    ) -> String;
     fn fmt_input(&self, payload: RenderPayload) -> String;
     fn fmt_submit(&self, value: Vec<Value>) -> String;
 }
@@ -24,22 +37,45 @@ impl ArrayFormatter for DefaultArrayFormatter {
         format!("{}{}", submitted, input)
     }
 
-    fn fmt_submitted_item(&self, _: usize, value: Value) -> String {
-        format!("{},\n", value)
+    fn fmt_submitted_item(&self, idx: usize, value: Value, error: Option<String>) -> String {
+        let state = match error {
+            Some(error) => Styled::new(format!("✗ {}", error))
+                .fg(Color::Red)
+                .to_string(),
+            None => Styled::new("✓").fg(Color::Green).to_string(),
+        };
+        format!("[{}] {}  {}\n", idx, value, state)
+    }
+
+    fn fmt_list_item(
+        &self,
+        idx: usize,
+        value: Value,
+        error: Option<String>,
+        selected: bool,
+    ) -> String {
+        let item = self.fmt_submitted_item(idx, value, error);
+        if selected {
+            format!("{} {}", Styled::new("›").fg(Color::Cyan), item)
+        } else {
+            format!("  {}", item)
+        }
     }
 
     fn fmt_input(&self, payload: RenderPayload) -> String {
-        let prompt_input = fmt_input(&payload.input);
+        let prompt_input = fmt_input(&payload.input, payload.placeholder.as_deref());
         let prompt_body = fmt_body(&payload.body);
+        let prompt_hint = Styled::new(payload.hint.map(|x| format!("{}\n", x)).unwrap_or_default())
+            .fg(Color::DarkGrey)
+            .to_string();
 
-        format!("{}\n{}", prompt_input, prompt_body)
+        format!("{}\n{}{}", prompt_input, prompt_body, prompt_hint)
     }
 
     fn fmt_submit(&self, value: Vec<Value>) -> String {
         let value = value
             .iter()
-            .enumerate()
-            .map(|(_, v)| format!("{}", v))
+            .map(|v| format!("{}", v))
             .collect::<Vec<_>>()
             .join(",\n");
 
@@ -63,13 +99,42 @@ impl PromptOption {
             unique_items: schema.unique_items,
         }
     }
+
+    pub fn min_items(&self) -> Option<usize> {
+        self.min_items
+    }
+
+    pub fn max_items(&self) -> Option<usize> {
+        self.max_items
+    }
+
+    pub fn unique_items(&self) -> bool {
+        self.unique_items
+    }
 }
 
 impl Validator<Vec<Value>> for PromptOption {
     fn validate(&self, value: &Vec<Value>) -> Result<(), String> {
         if let Some(min) = self.min_items {
             if value.len() < min {
-                return Err(format!("Array must have at least {} items", min));
+                return Err(format!("Array must have at least {} items (minItems)", min));
+            }
+        }
+
+        if let Some(max) = self.max_items {
+            if value.len() > max {
+                return Err(format!("Array must have at most {} items (maxItems)", max));
+            }
+        }
+
+        if self.unique_items {
+            for (idx, item) in value.iter().enumerate() {
+                if value[..idx].contains(item) {
+                    return Err(format!(
+                        "Array items must be unique: {} (uniqueItems)",
+                        item
+                    ));
+                }
             }
         }
 
@@ -77,13 +142,33 @@ impl Validator<Vec<Value>> for PromptOption {
     }
 }
 
+/// Whether an item is prompted or the items are listed to be managed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Mode {
+    Item,
+    List,
+}
+
 pub struct Array {
     message: String,
     option: PromptOption,
     formatter: Box<dyn ArrayFormatter>,
     api: OpenAPI,
     value: Vec<Value>,
-    current_prompt: Option<Box<dyn Prompt<Output = Value>>>,
+    default: Vec<Value>,
+    config: PromptConfig,
+    fixed: bool,
+    pending: VecDeque<usize>,
+    /// Indices prompted so far, in order, to go back through.
+    answered: Vec<usize>,
+    mode: Mode,
+    /// The selected item in the list.
+    cursor: usize,
+    /// Whether the current prompt is opened from the list, which is shown again after it.
+    from_list: bool,
+    current_prompt: Option<(usize, Box<dyn Prompt<Output = Value>>)>,
+    /// The first violation of each item, kept for the items which are not changed between renders.
+    violations: Vec<(Value, Option<String>)>,
 }
 
 impl Array {
@@ -94,60 +179,300 @@ impl Array {
             formatter: Box::new(DefaultArrayFormatter),
             api: api.clone(),
             value: Vec::new(),
+            default: Vec::new(),
+            config: PromptConfig::default(),
+            fixed: false,
+            pending: VecDeque::new(),
+            answered: Vec::new(),
+            mode: Mode::Item,
+            cursor: 0,
+            from_list: false,
             current_prompt: None,
+            violations: Vec::new(),
         }
     }
 
-    fn create_prompt(&mut self) -> Result<(), promptuity::Error> {
-        match &self.option.items {
-            Some(refs) => {
-                let refs = refs.clone().unbox();
-                let item = refs
-                    .item(&self.api)
-                    .map_err(|x| promptuity::Error::Config(x.to_string()))?;
+    pub fn with_formatter(&mut self, formatter: impl ArrayFormatter + 'static) -> &mut Self {
+        self.formatter = Box::new(formatter);
+        self
+    }
+
+    pub fn with_config(&mut self, config: PromptConfig) -> &mut Self {
+        self.config = config;
+        self
+    }
 
-                let idx = self.value.len();
-                let msg = format!("{}[{}]", self.message, idx);
-                let mut prompt = prompt_builder(
-                    &self.api,
-                    item,
-                    msg,
-                    item.schema_data.description.clone(),
-                    None,
-                );
-                prompt.setup()?;
-                self.current_prompt = Some(prompt);
+    /// Items given up front. Only the incomplete ones are prompted and no items are added.
+    pub fn with_value(&mut self, value: Vec<Value>) -> &mut Self {
+        self.value = value;
+        self.fixed = true;
+        self
+    }
 
-                Ok(())
-            }
+    pub fn with_default(&mut self, value: Vec<Value>) -> &mut Self {
+        self.default = value;
+        self
+    }
+
+    fn item_schema(&self) -> Result<Schema, promptuity::Error> {
+        match &self.option.items {
+            Some(refs) => refs
+                .clone()
+                .unbox()
+                .item(&self.api)
+                .cloned()
+                .map_err(|x| promptuity::Error::Config(x.to_string())),
             None => Err(promptuity::Error::Config(
                 "No items schema found".to_owned(),
             )),
         }
     }
 
-    fn check_unique(&self, value: &Value) -> bool {
+    fn is_full(&self) -> bool {
+        self.option
+            .max_items
+            .is_some_and(|max| self.value.len() >= max)
+    }
+
+    /// Returns false when there is nothing left to prompt.
+    fn create_prompt(&mut self) -> Result<bool, promptuity::Error> {
+        let idx = match self.pending.pop_front() {
+            Some(idx) => idx,
+            None if self.fixed || self.is_full() => return Ok(false),
+            None => self.value.len(),
+        };
+        self.prompt_at(idx)?;
+
+        Ok(true)
+    }
+
+    /// Prompts the item at `idx`. An answered item is pre-filled to be edited,
+    /// while an incomplete one is completed.
+    fn prompt_at(&mut self, idx: usize) -> Result<(), promptuity::Error> {
+        let item = self.item_schema()?;
+        let current = self.value.get(idx).cloned();
+        let answered = match &current {
+            Some(value) => is_answered(&self.api, &item, value, &self.config)
+                .map_err(|x| promptuity::Error::Config(x.to_string()))?,
+            None => false,
+        };
+        let (value, default) = if answered {
+            (None, current)
+        } else {
+            (current, self.default.get(idx).cloned())
+        };
+
+        self.open_prompt(idx, value, default)
+    }
+
+    fn open_prompt(
+        &mut self,
+        idx: usize,
+        value: Option<Value>,
+        default: Option<Value>,
+    ) -> Result<(), promptuity::Error> {
+        let item = self.item_schema()?;
+        let msg = format!("{}[{}]", self.message, idx);
+        let mut prompt = prompt_builder(
+            &self.api,
+            &item,
+            msg,
+            item.schema_data.description.clone(),
+            value,
+            default,
+            &self.config,
+        );
+        prompt.setup()?;
+        self.mode = Mode::Item;
+        self.current_prompt = Some((idx, prompt));
+
+        Ok(())
+    }
+
+    /// Goes back to the previous item, or tells the parent to when this is the first one.
+    fn back(&mut self) -> PromptState {
+        let Some(idx) = self.answered.pop() else {
+            self.config.back.set(true);
+            return PromptState::Submit;
+        };
+
+        if let (Mode::Item, Some((current, _))) = (self.mode, &self.current_prompt) {
+            if *current < self.value.len() {
+                self.pending.push_front(*current);
+            }
+        }
+        match self.prompt_at(idx) {
+            Ok(()) => PromptState::Active,
+            Err(err) => PromptState::Fatal(err.to_string()),
+        }
+    }
+
+    fn show_list(&mut self, cursor: usize) -> PromptState {
+        self.mode = Mode::List;
+        self.from_list = false;
+        self.current_prompt = None;
+        self.cursor = cursor.min(self.value.len().saturating_sub(1));
+        PromptState::Active
+    }
+
+    /// Whether the value is not in the array yet, apart from the item at `except`.
+    fn check_unique(&self, value: &Value, except: Option<usize>) -> bool {
         if !self.option.unique_items {
             return true;
         }
 
-        !self.value.contains(value)
+        !self
+            .value
+            .iter()
+            .enumerate()
+            .any(|(idx, x)| Some(idx) != except && x == value)
     }
-}
 
-impl Prompt for Array {
-    type Output = Value;
+    /// The first violation of the item, for the list.
+    fn item_error(&self, idx: usize) -> Option<String> {
+        let value = &self.value[idx];
+        if self.option.unique_items && self.value[..idx].contains(value) {
+            return Some("Array items must be unique (uniqueItems)".to_owned());
+        }
 
-    fn setup(&mut self) -> Result<(), promptuity::Error> {
-        self.create_prompt()
+        self.violations
+            .get(idx)
+            .and_then(|(_, error)| error.clone())
     }
 
-    fn handle(&mut self, code: KeyCode, modifiers: KeyModifiers) -> PromptState {
+    /// Validates the items changed since the last render, so the others are not checked on every key.
+    fn refresh_violations(&mut self) {
+        let mut cached = std::mem::take(&mut self.violations);
+        let mut item = None;
+        let mut violations = Vec::with_capacity(self.value.len());
+        for value in &self.value {
+            let violation = match cached.iter().position(|(x, _)| x == value) {
+                Some(pos) => cached.swap_remove(pos),
+                None => {
+                    let item = item.get_or_insert_with(|| self.item_schema().ok());
+                    let error = item.as_ref().and_then(|item| self.violation(item, value));
+                    (value.clone(), error)
+                }
+            };
+            violations.push(violation);
+        }
+        self.violations = violations;
+    }
+
+    fn violation(&self, item: &Schema, value: &Value) -> Option<String> {
+        let violations = validate::validate(&self.api, item, value, "").ok()?;
+        violations.first().map(|x| match x.path.as_str() {
+            "" => x.message.clone(),
+            path => format!("{}: {}", path.trim_start_matches('.'), x.message),
+        })
+    }
+
+    fn handle_list(&mut self, code: KeyCode, modifiers: KeyModifiers) -> PromptState {
+        let len = self.value.len();
+        match (code, modifiers) {
+            // An empty list finishes the array with `<Esc>` too.
+            (KeyCode::Esc, _) => match self.option.validate(&self.value) {
+                Ok(()) => PromptState::Submit,
+                Err(err) => PromptState::Error(err),
+            },
+            (KeyCode::Up, KeyModifiers::SHIFT) | (KeyCode::Char('K'), _) if self.cursor > 0 => {
+                self.value.swap(self.cursor, self.cursor - 1);
+                self.answered.clear();
+                self.cursor -= 1;
+                PromptState::Active
+            }
+            (KeyCode::Down, KeyModifiers::SHIFT) | (KeyCode::Char('J'), _)
+                if self.cursor + 1 < len =>
+            {
+                self.value.swap(self.cursor, self.cursor + 1);
+                self.answered.clear();
+                self.cursor += 1;
+                PromptState::Active
+            }
+            (KeyCode::Up, _)
+            | (KeyCode::Char('k'), KeyModifiers::NONE)
+            | (KeyCode::Char('p'), KeyModifiers::CONTROL) => {
+                self.cursor = self.cursor.saturating_sub(1);
+                PromptState::Active
+            }
+            (KeyCode::Down, _)
+            | (KeyCode::Char('j'), KeyModifiers::NONE)
+            | (KeyCode::Char('n'), KeyModifiers::CONTROL) => {
+                self.cursor = (self.cursor + 1).min(len.saturating_sub(1));
+                PromptState::Active
+            }
+            (KeyCode::Enter, _) if len > 0 => {
+                let state = match self.prompt_at(self.cursor) {
+                    Ok(()) => PromptState::Active,
+                    Err(err) => PromptState::Fatal(err.to_string()),
+                };
+                self.from_list = true;
+                state
+            }
+            (KeyCode::Char('a'), KeyModifiers::NONE) | (KeyCode::Char('c'), KeyModifiers::NONE)
+                if self.is_full() =>
+            {
+                PromptState::Error("Array is full (maxItems)".to_owned())
+            }
+            (KeyCode::Char('a'), KeyModifiers::NONE) => {
+                let state = match self.open_prompt(len, None, None) {
+                    Ok(()) => PromptState::Active,
+                    Err(err) => PromptState::Fatal(err.to_string()),
+                };
+                self.from_list = true;
+                state
+            }
+            // The selected item is copied as a template of a new item.
+            (KeyCode::Char('c'), KeyModifiers::NONE) if len > 0 => {
+                let template = self.value[self.cursor].clone();
+                let state = match self.open_prompt(len, None, Some(template)) {
+                    Ok(()) => PromptState::Active,
+                    Err(err) => PromptState::Fatal(err.to_string()),
+                };
+                self.from_list = true;
+                state
+            }
+            (KeyCode::Char('d'), KeyModifiers::NONE) | (KeyCode::Delete, _) if len > 0 => {
+                self.value.remove(self.cursor);
+                self.answered.clear();
+                self.show_list(self.cursor)
+            }
+            _ => PromptState::Active,
+        }
+    }
+
+    fn handle_key(&mut self, code: KeyCode, modifiers: KeyModifiers) -> PromptState {
         if let (KeyCode::Char('c'), KeyModifiers::CONTROL) = (code, modifiers) {
             return PromptState::Cancel;
         }
 
-        let prompt = self.current_prompt.as_mut().unwrap();
+        if code == KeyCode::BackTab {
+            let nested = self.item_schema().is_ok_and(|x| is_nested(&x));
+            if let (true, Some((_, prompt))) = (nested, self.current_prompt.as_mut()) {
+                let state = prompt.handle(code, modifiers);
+                if !self.config.back.take() {
+                    return state;
+                }
+            }
+            return self.back();
+        }
+
+        // Only object items have optional properties to skip.
+        if let (KeyCode::Char('s'), KeyModifiers::CONTROL) = (code, modifiers) {
+            if !self.item_schema().is_ok_and(|x| is_nested(&x)) {
+                return PromptState::Active;
+            }
+        }
+
+        let Some((idx, prompt)) = self.current_prompt.as_mut() else {
+            return self.handle_list(code, modifiers);
+        };
+        let idx = *idx;
+
+        // Object items don't give `<Esc>` back while a required field is left, so the list has its own key.
+        if let (KeyCode::Char('l'), KeyModifiers::CONTROL) = (code, modifiers) {
+            return self.show_list(idx);
+        }
 
         let state = prompt.handle(code, modifiers);
         match state {
@@ -158,59 +483,175 @@ impl Prompt for Array {
                 }
 
                 let value = prompt.submit();
-                if self.check_unique(&value) {
-                    return PromptState::Error("Value already exists".to_owned());
+                if !self.check_unique(&value, Some(idx)) {
+                    return PromptState::Error(format!(
+                        "Value already exists: {} (uniqueItems)",
+                        value
+                    ));
                 }
-                if let Some(max) = self.option.max_items {
-                    if self.value.len() >= max {
-                        return PromptState::Error("Array is full".to_owned());
+                if idx < self.value.len() {
+                    self.value[idx] = value;
+                } else {
+                    if self.is_full() {
+                        return PromptState::Error("Array is full (maxItems)".to_owned());
                     }
-                }
 
-                self.value.push(value);
+                    self.value.push(value);
+                }
+                self.answered.push(idx);
 
+                if self.from_list {
+                    return self.show_list(idx);
+                }
                 match self.create_prompt() {
-                    Ok(_) => PromptState::Active,
+                    Ok(true) => PromptState::Active,
+                    Ok(false) => PromptState::Submit,
                     Err(err) => PromptState::Fatal(err.to_string()),
                 }
             }
+            // `<Esc>` lists the items to be managed, and finishes the array from the list.
+            PromptState::Cancel if self.from_list || !self.value.is_empty() => {
+                self.show_list(self.value.len().min(idx))
+            }
             PromptState::Cancel => PromptState::Submit,
             _ => state,
         }
     }
+}
+
+impl Prompt for Array {
+    type Output = Value;
+
+    fn setup(&mut self) -> Result<(), promptuity::Error> {
+        if self.fixed {
+            let item = self.item_schema()?;
+            for (idx, value) in self.value.iter().enumerate() {
+                let answered = is_answered(&self.api, &item, value, &self.config)
+                    .map_err(|x| promptuity::Error::Config(x.to_string()))?;
+                if !answered {
+                    self.pending.push_back(idx);
+                }
+            }
+        }
+
+        if !self.create_prompt()? {
+            self.show_list(0);
+        }
+        self.config
+            .esc
+            .set(self.mode == Mode::List || !self.value.is_empty());
+
+        Ok(())
+    }
+
+    fn handle(&mut self, code: KeyCode, modifiers: KeyModifiers) -> PromptState {
+        let state = self.handle_key(code, modifiers);
+        let esc = self.mode == Mode::List || self.from_list || !self.value.is_empty();
+        self.config
+            .esc
+            .set(esc && matches!(state, PromptState::Active | PromptState::Error(_)));
+        state
+    }
 
     fn submit(&mut self) -> Self::Output {
         json!(self.value)
     }
 
     fn render(&mut self, state: &PromptState) -> Result<RenderPayload, String> {
-        match state {
-            PromptState::Submit => Ok(RenderPayload::new(self.message.clone(), None, None).body(
-                PromptBody::Raw(self.formatter.fmt_submit(self.value.clone())),
-            )),
-            _ => {
-                let prompt = self.current_prompt.as_mut().unwrap();
+        if let PromptState::Submit = state {
+            return Ok(
+                RenderPayload::new(self.message.clone(), None, None).body(PromptBody::Raw(
+                    self.formatter.fmt_submit(self.value.clone()),
+                )),
+            );
+        }
 
-                let prompt_payload = prompt.render(state)?;
+        self.refresh_violations();
+        let errors = (0..self.value.len())
+            .map(|idx| self.item_error(idx))
+            .collect::<Vec<_>>();
+        let body = match self.current_prompt.as_mut() {
+            Some((_, prompt)) => {
+                let mut prompt_payload = prompt.render(state)?;
+                let hint = "<C-l> to list items".to_owned();
+                prompt_payload.hint = Some(match prompt_payload.hint {
+                    Some(x) => format!("{}, {}", x, hint),
+                    None => hint,
+                });
                 let input = self.formatter.fmt_input(prompt_payload);
 
                 let submitted = self
                     .value
                     .iter()
+                    .zip(errors)
                     .enumerate()
-                    .map(|(idx, value)| self.formatter.fmt_submitted_item(idx, value.clone()))
+                    .map(|(idx, (value, error))| {
+                        self.formatter.fmt_submitted_item(idx, value.clone(), error)
+                    })
                     .collect::<Vec<_>>()
                     .join("");
-
-                let payload = RenderPayload::new(self.message.clone(), None, None)
-                    .body(PromptBody::Raw(self.formatter.fmt_prompt(submitted, input)));
-
-                Ok(payload)
+                self.formatter.fmt_prompt(submitted, input)
             }
-        }
+            None => {
+                let items = self
+                    .value
+                    .iter()
+                    .zip(errors)
+                    .enumerate()
+                    .map(|(idx, (value, error))| {
+                        self.formatter
+                            .fmt_list_item(idx, value.clone(), error, idx == self.cursor)
+                    })
+                    .collect::<Vec<_>>()
+                    .join("");
+                let hint = Styled::new(
+                    "<Enter> edit, a add, c duplicate, d remove, K/J move, <Esc> done\n",
+                )
+                .fg(Color::DarkGrey)
+                .to_string();
+                format!("{}{}", items, hint)
+            }
+        };
+
+        Ok(RenderPayload::new(self.message.clone(), None, None).body(PromptBody::Raw(body)))
     }
 
     fn validate(&self) -> Result<(), String> {
         self.option.validate(&self.value)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn array(schema: Value) -> Array {
+        let schema = serde_json::from_value::<ArrayType>(schema).unwrap();
+        Array::new("tags".to_owned(), &OpenAPI::default(), schema)
+    }
+
+    #[test]
+    fn item_errors() {
+        let mut prompt = array(json!({
+            "type": "array",
+            "uniqueItems": true,
+            "items": {"type": "string", "pattern": "^[a-z]+$"}
+        }));
+        prompt.value = vec![json!("a"), json!("B"), json!("a")];
+        prompt.refresh_violations();
+        assert_eq!(prompt.item_error(0), None);
+        assert_eq!(
+            prompt.item_error(1).unwrap(),
+            "Value does not match pattern: ^[a-z]+$ (pattern)"
+        );
+        assert_eq!(
+            prompt.item_error(2).unwrap(),
+            "Array items must be unique (uniqueItems)"
+        );
+
+        prompt.value = vec![json!("b"), json!("a")];
+        prompt.refresh_violations();
+        assert_eq!(prompt.item_error(0), None);
+        assert_eq!(prompt.item_error(1), None);
+    }
+}
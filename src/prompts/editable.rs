@@ -0,0 +1,89 @@
+use promptuity::event::{KeyCode, KeyModifiers};
+use promptuity::{Prompt, PromptInput, PromptState, RenderPayload};
+use serde_json::Value;
+
+/// The answer of an [`Editable`] prompt.
+pub enum Edit {
+    Done(Value),
+    /// `$EDITOR` is requested, with the fields answered so far by an object or an array.
+    Editor(Option<Value>),
+}
+
+/// Wraps the body prompt, so `<C-e>` stops prompting to edit the value in `$EDITOR` instead.
+pub struct Editable {
+    prompt: Box<dyn Prompt<Output = Value>>,
+    nested: bool,
+    editor: bool,
+}
+
+impl Editable {
+    pub fn from_boxed(prompt: Box<dyn Prompt<Output = Value>>) -> Self {
+        Self {
+            prompt,
+            nested: false,
+            editor: false,
+        }
+    }
+
+    /// Whether the prompt is an object or an array, whose partial answer is kept.
+    pub fn with_nested(&mut self, nested: bool) -> &mut Self {
+        self.nested = nested;
+        self
+    }
+}
+
+impl Prompt for Editable {
+    type Output = Edit;
+
+    fn setup(&mut self) -> Result<(), promptuity::Error> {
+        self.prompt.setup()
+    }
+
+    fn handle(&mut self, code: KeyCode, modifiers: KeyModifiers) -> PromptState {
+        match (code, modifiers) {
+            (KeyCode::Char('e'), KeyModifiers::CONTROL) => {
+                self.editor = true;
+                PromptState::Submit
+            }
+            _ => self.prompt.handle(code, modifiers),
+        }
+    }
+
+    fn submit(&mut self) -> Self::Output {
+        match (self.editor, self.nested) {
+            (true, true) => Edit::Editor(Some(self.prompt.submit())),
+            (true, false) => Edit::Editor(None),
+            (false, _) => Edit::Done(self.prompt.submit()),
+        }
+    }
+
+    fn render(&mut self, state: &PromptState) -> Result<RenderPayload, String> {
+        let prompt = self.prompt.render(state)?;
+
+        match state {
+            PromptState::Submit if self.editor => {
+                Ok(RenderPayload::new(prompt.message.clone(), None, None)
+                    .input(PromptInput::Raw("$EDITOR".to_owned())))
+            }
+            PromptState::Submit => Ok(prompt),
+            _ => Ok(RenderPayload::new(
+                prompt.message.clone(),
+                prompt
+                    .hint
+                    .map(|x| format!("{}, <C-e> to open $EDITOR", x))
+                    .or(Some("<C-e> to open $EDITOR".to_owned())),
+                prompt.placeholder,
+            )
+            .input(prompt.input)
+            .body(prompt.body)),
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.editor {
+            Ok(())
+        } else {
+            self.prompt.validate()
+        }
+    }
+}
@@ -18,6 +18,7 @@ where
     message: String,
     page_size: usize,
     options: Vec<SelectOption<T>>,
+    keywords: Vec<String>,
     filtered_options: Vec<usize>,
     index: usize,
     input: InputCursor,
@@ -34,6 +35,7 @@ where
             message,
             page_size: 8,
             options,
+            keywords: Vec::new(),
             filtered_options: Vec::new(),
             index: 0,
             input: InputCursor::default(),
@@ -41,6 +43,19 @@ where
         }
     }
 
+    pub fn with_default(&mut self, f: impl Fn(&T) -> bool) -> &mut Self {
+        if let Some(idx) = self.options.iter().position(|x| f(&x.value)) {
+            self.index = idx;
+        }
+        self
+    }
+
+    /// Extra text searched along with the label, one per option in the same order.
+    pub fn with_keywords(&mut self, keywords: Vec<String>) -> &mut Self {
+        self.keywords = keywords;
+        self
+    }
+
     fn run_filter(&mut self) {
         let pattern = self.input.value();
 
@@ -48,7 +63,13 @@ where
             .options
             .iter()
             .enumerate()
-            .filter_map(|(i, option)| self.matcher.fuzzy_match(&option.label, &pattern).map(|_| i))
+            .filter_map(|(i, option)| {
+                let text = match self.keywords.get(i) {
+                    Some(keywords) => format!("{} {}", option.label, keywords),
+                    None => option.label.clone(),
+                };
+                self.matcher.fuzzy_match(&text, &pattern).map(|_| i)
+            })
             .collect::<Vec<_>>();
 
         self.index = std::cmp::min(self.filtered_options.len().saturating_sub(1), self.index);
@@ -1,4 +0,0 @@
-use thiserror::Error;
-
-#[derive(Error, Debug)]
-pub enum PromptError {}
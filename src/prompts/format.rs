@@ -0,0 +1,259 @@
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+use chrono::{DateTime, Duration, NaiveDate, SecondsFormat, TimeZone};
+use openapiv3::{StringFormat, VariantOrUnknownOrEmpty};
+
+/// The `format`s of strings which are checked.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Date,
+    DateTime,
+    Uuid,
+    Email,
+    Uri,
+    Hostname,
+    Ipv4,
+    Ipv6,
+}
+
+impl Format {
+    pub fn from_schema(format: &VariantOrUnknownOrEmpty<StringFormat>) -> Option<Self> {
+        match format {
+            VariantOrUnknownOrEmpty::Item(StringFormat::Date) => Some(Self::Date),
+            VariantOrUnknownOrEmpty::Item(StringFormat::DateTime) => Some(Self::DateTime),
+            VariantOrUnknownOrEmpty::Unknown(x) => match x.as_str() {
+                "uuid" => Some(Self::Uuid),
+                "email" => Some(Self::Email),
+                "uri" => Some(Self::Uri),
+                "hostname" => Some(Self::Hostname),
+                "ipv4" => Some(Self::Ipv4),
+                "ipv6" => Some(Self::Ipv6),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    pub fn check(&self, value: &str) -> Result<(), String> {
+        let valid = match self {
+            Self::Date => value.len() == 10 && NaiveDate::parse_from_str(value, "%Y-%m-%d").is_ok(),
+            Self::DateTime => DateTime::parse_from_rfc3339(value).is_ok(),
+            Self::Uuid => value.len() == 36 && uuid::Uuid::try_parse(value).is_ok(),
+            Self::Email => match value.rsplit_once('@') {
+                Some((local, domain)) => {
+                    !local.is_empty()
+                        && !local.chars().any(|x| x.is_whitespace() || x == '@')
+                        && domain.contains('.')
+                        && is_hostname(domain)
+                }
+                None => false,
+            },
+            Self::Uri => url::Url::parse(value).is_ok(),
+            Self::Hostname => is_hostname(value),
+            Self::Ipv4 => value.parse::<Ipv4Addr>().is_ok(),
+            Self::Ipv6 => value.parse::<Ipv6Addr>().is_ok(),
+        };
+        if valid {
+            return Ok(());
+        }
+
+        let (name, example) = match self {
+            Self::Date => ("date", "2024-01-31"),
+            Self::DateTime => ("date-time", "2024-01-31T09:00:00Z"),
+            Self::Uuid => ("uuid", "123e4567-e89b-12d3-a456-426614174000"),
+            Self::Email => ("email", "user@example.com"),
+            Self::Uri => ("uri", "https://example.com/path"),
+            Self::Hostname => ("hostname", "api.example.com"),
+            Self::Ipv4 => ("ipv4", "192.0.2.1"),
+            Self::Ipv6 => ("ipv6", "2001:db8::1"),
+        };
+        Err(format!(
+            "Value is not a valid {}, e.g. {} (format)",
+            name, example
+        ))
+    }
+
+    /// Resolves `now`, `today`, `tomorrow`, `yesterday` and offsets such as `+1d` or `-2h`
+    /// of dates and date-times in the time zone of `now`. Other input is returned as `None`.
+    pub fn expand<Tz: TimeZone>(&self, input: &str, now: DateTime<Tz>) -> Option<String>
+    where
+        Tz::Offset: std::fmt::Display,
+    {
+        let input = input.trim();
+        let at = match input {
+            "now" | "today" => now,
+            "tomorrow" => now.checked_add_signed(Duration::days(1))?,
+            "yesterday" => now.checked_sub_signed(Duration::days(1))?,
+            _ => now.checked_add_signed(offset(input)?)?,
+        };
+
+        match self {
+            Self::Date => Some(at.format("%Y-%m-%d").to_string()),
+            // `today` and the days around it start at midnight.
+            Self::DateTime if matches!(input, "today" | "tomorrow" | "yesterday") => Some(
+                at.date_naive()
+                    .and_hms_opt(0, 0, 0)?
+                    .and_local_timezone(at.timezone())
+                    .earliest()?
+                    .to_rfc3339_opts(SecondsFormat::Secs, true),
+            ),
+            Self::DateTime => Some(at.to_rfc3339_opts(SecondsFormat::Secs, true)),
+            _ => None,
+        }
+    }
+
+    /// Keys and words the prompt of the format accepts.
+    pub fn hint(&self) -> Option<&'static str> {
+        match self {
+            Self::Date => Some("today, +1d, -1w"),
+            Self::DateTime => Some("now, today, +1h, -30m"),
+            Self::Uuid => Some("<C-g> to generate"),
+            _ => None,
+        }
+    }
+}
+
+/// A random (v4) UUID.
+pub fn uuid() -> String {
+    uuid::Uuid::new_v4().to_string()
+}
+
+/// `+1d`, `-2h`, `+30m`, `+10s`, `+1w`
+fn offset(input: &str) -> Option<Duration> {
+    let (sign, rest) = match input.split_at_checked(1)? {
+        ("+", rest) => (1, rest),
+        ("-", rest) => (-1, rest),
+        _ => return None,
+    };
+    let (amount, unit) = rest.split_at_checked(rest.len().checked_sub(1)?)?;
+    if amount.is_empty() || !amount.bytes().all(|x| x.is_ascii_digit()) {
+        return None;
+    }
+    let amount = sign * amount.parse::<i64>().ok()?;
+
+    match unit {
+        "s" => Duration::try_seconds(amount),
+        "m" => Duration::try_minutes(amount),
+        "h" => Duration::try_hours(amount),
+        "d" => Duration::try_days(amount),
+        "w" => Duration::try_weeks(amount),
+        _ => None,
+    }
+}
+
+/// RFC 1123 host names: dot separated labels of letters, digits and hyphens.
+fn is_hostname(value: &str) -> bool {
+    let value = value.strip_suffix('.').unwrap_or(value);
+    !value.is_empty()
+        && value.len() <= 253
+        && value.split('.').all(|label| {
+            !label.is_empty()
+                && label.len() <= 63
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.chars().all(|x| x.is_ascii_alphanumeric() || x == '-')
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::FixedOffset;
+
+    use super::*;
+
+    fn now() -> DateTime<FixedOffset> {
+        DateTime::parse_from_rfc3339("2024-01-31T23:30:00+09:00").unwrap()
+    }
+
+    #[test]
+    fn expand_dates() {
+        let expand = |input| Format::Date.expand(input, now());
+        assert_eq!(expand("today").as_deref(), Some("2024-01-31"));
+        assert_eq!(expand(" tomorrow ").as_deref(), Some("2024-02-01"));
+        assert_eq!(expand("yesterday").as_deref(), Some("2024-01-30"));
+        assert_eq!(expand("+1w").as_deref(), Some("2024-02-07"));
+        assert_eq!(expand("-31d").as_deref(), Some("2023-12-31"));
+        assert_eq!(expand("+1h").as_deref(), Some("2024-02-01"));
+    }
+
+    #[test]
+    fn expand_date_times() {
+        let expand = |input| Format::DateTime.expand(input, now());
+        assert_eq!(expand("now").as_deref(), Some("2024-01-31T23:30:00+09:00"));
+        assert_eq!(
+            expand("today").as_deref(),
+            Some("2024-01-31T00:00:00+09:00")
+        );
+        assert_eq!(
+            expand("tomorrow").as_deref(),
+            Some("2024-02-01T00:00:00+09:00")
+        );
+        assert_eq!(expand("+30m").as_deref(), Some("2024-02-01T00:00:00+09:00"));
+        assert_eq!(expand("-10s").as_deref(), Some("2024-01-31T23:29:50+09:00"));
+        let utc = Format::DateTime.expand("today", now().to_utc());
+        assert_eq!(utc.as_deref(), Some("2024-01-31T00:00:00Z"));
+    }
+
+    #[test]
+    fn expand_other_input() {
+        for input in [
+            "",
+            "2024-01-31",
+            "+",
+            "+d",
+            "1d",
+            "+1",
+            "+1y",
+            "+1.5d",
+            "+-1d",
+            "+ 1d",
+            "+1é",
+            "+99999999999d",
+            "-99999999w",
+        ] {
+            assert_eq!(Format::Date.expand(input, now()), None, "{input}");
+        }
+        assert_eq!(Format::Uuid.expand("now", now()), None);
+    }
+
+    #[test]
+    fn check_formats() {
+        let valid = [
+            (Format::Date, "2024-02-29"),
+            (Format::DateTime, "2024-01-31T09:00:00.5+09:00"),
+            (Format::Uuid, "123e4567-e89b-12d3-a456-426614174000"),
+            (Format::Email, "first.last+tag@mail.example.com"),
+            (Format::Uri, "urn:isbn:0451450523"),
+            (Format::Hostname, "api-1.example.com."),
+            (Format::Ipv4, "192.0.2.1"),
+            (Format::Ipv6, "::ffff:192.0.2.1"),
+        ];
+        for (format, value) in valid {
+            assert_eq!(format.check(value), Ok(()), "{value}");
+        }
+
+        let invalid = [
+            (Format::Date, "2023-02-29"),
+            (Format::Date, "2024-1-5"),
+            (Format::Date, "2024-01-31T00:00:00Z"),
+            (Format::DateTime, "2024-01-31T09:00:00"),
+            (Format::DateTime, "2024-01-31"),
+            (Format::Uuid, "123e4567e89b12d3a456426614174000"),
+            (Format::Uuid, "{123e4567-e89b-12d3-a456-426614174000}"),
+            (Format::Email, "user@localhost"),
+            (Format::Email, "@example.com"),
+            (Format::Email, "a b@example.com"),
+            (Format::Uri, "/relative/path"),
+            (Format::Hostname, "-api.example.com"),
+            (Format::Hostname, "a..b"),
+            (Format::Hostname, &"a".repeat(64)),
+            (Format::Ipv4, "192.0.2.256"),
+            (Format::Ipv4, "192.0.2.01"),
+            (Format::Ipv6, "2001:db8:::1"),
+        ];
+        for (format, value) in invalid {
+            let err = format.check(value).expect_err(value);
+            assert!(err.ends_with("(format)"), "{value}: {err}");
+        }
+    }
+}
@@ -1,6 +1,8 @@
+use std::{cell::Cell, rc::Rc};
+
 use array::Array;
 use boolean::Boolean;
-use indexmap::IndexMap;
+use nullable::Nullable;
 use number::Number;
 use object::Object;
 use openapiv3::{OpenAPI, Schema, SchemaKind, Type};
@@ -9,91 +11,187 @@ use serde_json::Value;
 use skippable::Skippable;
 use string::StringPrompt;
 
+use crate::schema::{error::SchemaError, reference::ReferenceOrExt};
+
 pub mod array;
 pub mod boolean;
+pub mod editable;
 pub mod enumeration;
-pub(crate) mod error;
+pub mod format;
+pub mod navigable;
+pub mod nullable;
 pub mod number;
 pub mod object;
 pub mod skippable;
 pub mod string;
 pub(crate) mod utils;
 
-pub fn optional_prompt_builder(
-    api: &OpenAPI,
-    schema: &Schema,
-    message: String,
-    hint: Option<String>,
-    default: Option<IndexMap<String, Value>>,
-) -> Box<dyn Prompt<Output = Option<Value>>> {
-    match &schema.schema_kind {
-        SchemaKind::Type(Type::Boolean(_)) => {
-            let mut p = Boolean::new(message);
-            if let Some(hint) = hint {
-                p.with_hint(hint);
-            };
+/// Options shared by the prompts of a request, passed down to nested schemas.
+#[derive(Debug, Clone, Default)]
+pub struct PromptConfig {
+    /// Leave deprecated optional properties out of the prompts.
+    pub hide_deprecated: bool,
+    /// Prompt `readOnly` properties too, for APIs which misuse the keyword.
+    pub include_read_only: bool,
+    /// Leave every optional property out of the prompts.
+    pub required_only: bool,
+    /// Set by an object or an array when `<S-Tab>` is pressed on its first field,
+    /// so the prompt containing it goes back instead.
+    pub back: Rc<Cell<bool>>,
+    /// Set by an object or an array while it uses `<Esc>` itself,
+    /// so the [`Skippable`] around it passes the key on instead of skipping.
+    pub esc: Rc<Cell<bool>>,
+    /// Applied to the strings taken from the schema, such as defaults and enum values,
+    /// so the caller can tell them from typed values.
+    pub escape: Option<fn(&str) -> String>,
+}
 
-            Box::new(Skippable::new(p))
-        }
-        SchemaKind::Type(Type::String(string)) => {
-            let mut p = StringPrompt::new(message, string.clone().into());
-            if let Some(hint) = hint {
-                p.with_hint(hint);
-            };
+impl PromptConfig {
+    /// Whether the property is left out of the prompts.
+    pub fn skips(&self, schema: &Schema, required: bool) -> bool {
+        let data = &schema.schema_data;
+        (data.read_only && !self.include_read_only)
+            || (!required && (self.required_only || (self.hide_deprecated && data.deprecated)))
+    }
 
-            Box::new(Skippable::new(p))
-        }
-        SchemaKind::Type(Type::Number(number)) => {
-            let mut p = Number::new(message, number.clone().into());
-            if let Some(hint) = hint {
-                p.with_hint(hint);
-            };
+    /// A value of the schema with [`PromptConfig::escape`] applied to its strings.
+    pub fn escaped(&self, value: &Value) -> Value {
+        utils::escape_strings(value, self.escape)
+    }
+}
 
-            Box::new(Skippable::new(p))
-        }
-        SchemaKind::Type(Type::Integer(integer)) => {
-            let mut p = Number::new(message, integer.clone().into());
-            if let Some(hint) = hint {
-                p.with_hint(hint);
-            };
+/// Whether the schema is prompted field by field, by [`Object`] or [`Array`].
+pub fn is_nested(schema: &Schema) -> bool {
+    matches!(
+        schema.schema_kind,
+        SchemaKind::Type(Type::Object(_) | Type::Array(_))
+    )
+}
 
-            Box::new(Skippable::new(p))
-        }
-        SchemaKind::Type(Type::Object(object)) => {
-            let mut object = Object::new(message, api, object.clone());
-            if let Some(default) = default {
-                object.with_value(default);
+/// Whether the value given up front answers the schema, so no prompt is needed.
+/// Objects missing a property and arrays with such items are completed by the prompts.
+pub fn is_answered(
+    api: &OpenAPI,
+    schema: &Schema,
+    value: &Value,
+    config: &PromptConfig,
+) -> Result<bool, SchemaError> {
+    match (&schema.schema_kind, value) {
+        (SchemaKind::Type(Type::Object(object)), Value::Object(map)) => {
+            for (key, property) in &object.properties {
+                let property = property.clone().unbox();
+                let property = property.item(api)?;
+                match map.get(key) {
+                    Some(value) if !is_answered(api, property, value, config)? => return Ok(false),
+                    Some(_) => {}
+                    None if config.skips(property, object.required.contains(key)) => {}
+                    None => return Ok(false),
+                }
             }
-
-            Box::new(Skippable::new(object))
+            Ok(true)
         }
-        SchemaKind::Type(Type::Array(array)) => {
-            Box::new(Skippable::new(Array::new(message, api, array.clone())))
-        }
-        _ => unimplemented!(),
+        (SchemaKind::Type(Type::Array(array)), Value::Array(items)) => match &array.items {
+            Some(item) => {
+                let item = item.clone().unbox();
+                let item = item.item(api)?;
+                for value in items {
+                    if !is_answered(api, item, value, config)? {
+                        return Ok(false);
+                    }
+                }
+                Ok(true)
+            }
+            None => Ok(true),
+        },
+        _ => Ok(true),
     }
 }
 
+pub fn optional_prompt_builder(
+    api: &OpenAPI,
+    schema: &Schema,
+    message: String,
+    hint: Option<String>,
+    value: Option<Value>,
+    default: Option<Value>,
+    config: &PromptConfig,
+) -> Box<dyn Prompt<Output = Option<Value>>> {
+    let config = PromptConfig {
+        esc: Rc::new(Cell::new(false)),
+        ..config.clone()
+    };
+    let prompt = prompt_builder(api, schema, message, hint, value, default, &config);
+    let mut prompt = Skippable::from_boxed(prompt);
+    prompt.with_esc(config.esc);
+    Box::new(prompt)
+}
+
 pub fn prompt_builder(
     api: &OpenAPI,
     schema: &Schema,
     message: String,
     hint: Option<String>,
-    default: Option<IndexMap<String, Value>>,
+    value: Option<Value>,
+    default: Option<Value>,
+    config: &PromptConfig,
 ) -> Box<dyn Prompt<Output = Value>> {
+    let prompt = schema_prompt(api, schema, message, hint, value, default, config);
+    let primitive = matches!(
+        schema.schema_kind,
+        SchemaKind::Type(Type::Boolean(_) | Type::String(_) | Type::Number(_) | Type::Integer(_))
+    );
+    if primitive && schema.schema_data.nullable {
+        Box::new(Nullable::from_boxed(prompt))
+    } else {
+        prompt
+    }
+}
+
+fn schema_prompt(
+    api: &OpenAPI,
+    schema: &Schema,
+    message: String,
+    hint: Option<String>,
+    value: Option<Value>,
+    default: Option<Value>,
+    config: &PromptConfig,
+) -> Box<dyn Prompt<Output = Value>> {
+    let default = default.or_else(|| {
+        schema
+            .schema_data
+            .default
+            .as_ref()
+            .map(|x| config.escaped(x))
+    });
+    let example = schema.schema_data.example.as_ref().map(placeholder);
     match &schema.schema_kind {
         SchemaKind::Type(Type::Boolean(_)) => {
             let mut p = Boolean::new(message);
             if let Some(hint) = hint {
                 p.with_hint(hint);
             };
+            if let Some(default) = default.as_ref().and_then(|x| x.as_bool()) {
+                p.with_default(default);
+            }
             Box::new(p)
         }
         SchemaKind::Type(Type::String(string)) => {
-            let mut p = StringPrompt::new(message, string.clone().into());
+            let mut string = string.clone();
+            if let Some(escape) = config.escape {
+                for value in string.enumeration.iter_mut().flatten() {
+                    *value = escape(value);
+                }
+            }
+            let mut p = StringPrompt::new(message, string.into());
             if let Some(hint) = hint {
                 p.with_hint(hint);
             };
+            if let Some(example) = &example {
+                p.with_placeholder(example);
+            }
+            if let Some(default) = default.as_ref().and_then(|x| x.as_str()) {
+                p.with_default(default);
+            }
             Box::new(p)
         }
         SchemaKind::Type(Type::Number(number)) => {
@@ -101,6 +199,12 @@ pub fn prompt_builder(
             if let Some(hint) = hint {
                 p.with_hint(hint);
             };
+            if let Some(example) = &example {
+                p.with_placeholder(example);
+            }
+            if let Some(default) = default.as_ref().and_then(|x| x.as_number()) {
+                p.with_default(default);
+            }
             Box::new(p)
         }
         SchemaKind::Type(Type::Integer(integer)) => {
@@ -108,17 +212,47 @@ pub fn prompt_builder(
             if let Some(hint) = hint {
                 p.with_hint(hint);
             };
+            if let Some(example) = &example {
+                p.with_placeholder(example);
+            }
+            if let Some(default) = default.as_ref().and_then(|x| x.as_number()) {
+                p.with_default(default);
+            }
             Box::new(p)
         }
         SchemaKind::Type(Type::Object(object)) => {
             let mut object = Object::new(message, api, object.clone());
-            if let Some(default) = default {
-                object.with_value(default);
+            object.with_config(config.clone());
+            object.with_property_names(object::property_names(schema));
+            if let Some(value) = value.as_ref().and_then(|x| x.as_object()) {
+                object.with_value(value.clone().into_iter().collect());
+            }
+            if let Some(default) = default.as_ref().and_then(|x| x.as_object()) {
+                object.with_default(default.clone().into_iter().collect());
             }
 
             Box::new(object)
         }
-        SchemaKind::Type(Type::Array(array)) => Box::new(Array::new(message, api, array.clone())),
+        SchemaKind::Type(Type::Array(array)) => {
+            let mut array = Array::new(message, api, array.clone());
+            array.with_config(config.clone());
+            if let Some(value) = value.as_ref().and_then(|x| x.as_array()) {
+                array.with_value(value.clone());
+            }
+            if let Some(default) = default.as_ref().and_then(|x| x.as_array()) {
+                array.with_default(default.clone());
+            }
+
+            Box::new(array)
+        }
         _ => unimplemented!(),
     }
 }
+
+/// An example value as shown in an empty input.
+fn placeholder(value: &Value) -> String {
+    match value {
+        Value::String(x) => x.clone(),
+        x => x.to_string(),
+    }
+}
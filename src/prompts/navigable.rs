@@ -0,0 +1,92 @@
+use promptuity::event::{KeyCode, KeyModifiers};
+use promptuity::{Prompt, PromptInput, PromptState, RenderPayload};
+
+use super::PromptConfig;
+
+/// Wraps a top-level prompt, so `<S-Tab>` answers `None` to go back to the previous one.
+/// Objects and arrays go back through their own fields first.
+pub struct Navigable<O> {
+    prompt: Box<dyn Prompt<Output = O>>,
+    config: PromptConfig,
+    nested: bool,
+    back: bool,
+}
+
+impl<O> Navigable<O> {
+    pub fn from_boxed(prompt: Box<dyn Prompt<Output = O>>, config: &PromptConfig) -> Self {
+        Self {
+            prompt,
+            config: config.clone(),
+            nested: false,
+            back: false,
+        }
+    }
+
+    /// Whether the prompt is an object or an array, which handles `<S-Tab>` itself.
+    pub fn with_nested(&mut self, nested: bool) -> &mut Self {
+        self.nested = nested;
+        self
+    }
+}
+
+impl<O> Prompt for Navigable<O> {
+    type Output = Option<O>;
+
+    fn setup(&mut self) -> Result<(), promptuity::Error> {
+        self.config.back.set(false);
+        self.prompt.setup()
+    }
+
+    fn handle(&mut self, code: KeyCode, modifiers: KeyModifiers) -> PromptState {
+        if code != KeyCode::BackTab {
+            return self.prompt.handle(code, modifiers);
+        }
+
+        if self.nested {
+            let state = self.prompt.handle(code, modifiers);
+            if !self.config.back.take() {
+                return state;
+            }
+        }
+        self.back = true;
+        PromptState::Submit
+    }
+
+    fn submit(&mut self) -> Self::Output {
+        if self.back {
+            None
+        } else {
+            Some(self.prompt.submit())
+        }
+    }
+
+    fn render(&mut self, state: &PromptState) -> Result<RenderPayload, String> {
+        let prompt = self.prompt.render(state)?;
+
+        match state {
+            PromptState::Submit if self.back => {
+                Ok(RenderPayload::new(prompt.message.clone(), None, None)
+                    .input(PromptInput::Raw("Back".to_owned())))
+            }
+            PromptState::Submit => Ok(prompt),
+            _ => Ok(RenderPayload::new(
+                prompt.message.clone(),
+                prompt
+                    .hint
+                    .map(|x| format!("{}, <S-Tab> to go back", x))
+                    .or(Some("<S-Tab> to go back".to_owned())),
+                prompt.placeholder,
+            )
+            .input(prompt.input)
+            .body(prompt.body)),
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.back {
+            Ok(())
+        } else {
+            self.prompt.validate()
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use promptuity::event::{KeyCode, KeyModifiers};
+use promptuity::{Prompt, PromptInput, PromptState, RenderPayload};
+use serde_json::Value;
+
+/// Wraps the prompt of a `nullable` schema, so `<C-x>` answers `null`.
+pub struct Nullable {
+    prompt: Box<dyn Prompt<Output = Value>>,
+    null: bool,
+}
+
+impl Nullable {
+    pub fn from_boxed(prompt: Box<dyn Prompt<Output = Value>>) -> Self {
+        Self {
+            prompt,
+            null: false,
+        }
+    }
+}
+
+impl Prompt for Nullable {
+    type Output = Value;
+
+    fn setup(&mut self) -> Result<(), promptuity::Error> {
+        self.prompt.setup()
+    }
+
+    fn handle(&mut self, code: KeyCode, modifiers: KeyModifiers) -> PromptState {
+        match (code, modifiers) {
+            (KeyCode::Char('x'), KeyModifiers::CONTROL) => {
+                self.null = true;
+                PromptState::Submit
+            }
+            _ => self.prompt.handle(code, modifiers),
+        }
+    }
+
+    fn submit(&mut self) -> Self::Output {
+        if self.null {
+            Value::Null
+        } else {
+            self.prompt.submit()
+        }
+    }
+
+    fn render(&mut self, state: &PromptState) -> Result<RenderPayload, String> {
+        let prompt = self.prompt.render(state)?;
+
+        match state {
+            PromptState::Submit if self.null => {
+                Ok(RenderPayload::new(prompt.message.clone(), None, None)
+                    .input(PromptInput::Raw("null".to_owned())))
+            }
+            PromptState::Submit => Ok(prompt),
+            _ => Ok(RenderPayload::new(
+                prompt.message.clone(),
+                prompt
+                    .hint
+                    .map(|x| format!("{}, <C-x> for null", x))
+                    .or(Some("<C-x> for null".to_owned())),
+                prompt.placeholder,
+            )
+            .input(prompt.input)
+            .body(prompt.body)),
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.null {
+            Ok(())
+        } else {
+            self.prompt.validate()
+        }
+    }
+}
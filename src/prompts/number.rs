@@ -1,17 +1,98 @@
 use std::str::FromStr;
 
 use num_traits::Num;
-use openapiv3::{IntegerType, NumberType};
+use openapiv3::{IntegerFormat, IntegerType, NumberFormat, NumberType, VariantOrUnknownOrEmpty};
 use promptuity::{
     event::{KeyCode, KeyModifiers},
-    prompts::{NumberFormatter, SelectOption},
+    prompts::{Input, InputFormatter, NumberFormatter, SelectOption},
     Prompt, PromptState, RenderPayload, Validator,
 };
-use serde::Serialize;
 use serde_json::{json, Value};
 
 use super::enumeration::Enumeration;
 
+/// The types numbers are checked in: `i128` holds every `int64` and `uint64` exactly.
+pub trait Numeric: Num + PartialOrd + Clone + FromStr + ToString + 'static {
+    const INTEGER: bool;
+
+    fn is_multiple_of(&self, multiple: &Self) -> bool;
+    /// Whether [`Numeric::to_json`] keeps the value exact.
+    fn is_exact(&self) -> bool;
+    /// The value as JSON. Integers within `i64` or `u64` are kept exact; others are rounded to `f64`.
+    fn to_json(&self) -> Value;
+}
+
+impl Numeric for i128 {
+    const INTEGER: bool = true;
+
+    /// `i128::MIN % -1` overflows, though it is a multiple like any value of `0`.
+    fn is_multiple_of(&self, multiple: &Self) -> bool {
+        self.checked_rem(*multiple).is_none_or(|x| x == 0)
+    }
+
+    fn is_exact(&self) -> bool {
+        i64::try_from(*self).is_ok() || u64::try_from(*self).is_ok()
+    }
+
+    fn to_json(&self) -> Value {
+        match (i64::try_from(*self), u64::try_from(*self)) {
+            (Ok(x), _) => json!(x),
+            (_, Ok(x)) => json!(x),
+            _ => json!(*self as f64),
+        }
+    }
+}
+
+impl Numeric for f64 {
+    const INTEGER: bool = false;
+
+    /// Compared as decimals, since `0.07 % 0.01` is not `0` in binary floating point.
+    fn is_multiple_of(&self, multiple: &Self) -> bool {
+        if *multiple == 0.0 {
+            return true;
+        }
+        match (decimal(*self), decimal(*multiple)) {
+            (Some((digits, scale)), Some((multiple_digits, multiple_scale))) => {
+                let common = scale.max(multiple_scale);
+                match (
+                    10i128
+                        .checked_pow(common - scale)
+                        .and_then(|x| x.checked_mul(digits)),
+                    10i128
+                        .checked_pow(common - multiple_scale)
+                        .and_then(|x| x.checked_mul(multiple_digits)),
+                ) {
+                    (Some(a), Some(b)) => a % b == 0,
+                    _ => is_near_multiple(*self, *multiple),
+                }
+            }
+            _ => is_near_multiple(*self, *multiple),
+        }
+    }
+
+    fn is_exact(&self) -> bool {
+        true
+    }
+
+    fn to_json(&self) -> Value {
+        json!(self)
+    }
+}
+
+/// The shortest decimal which reads back as the float, as digits and the number of fraction digits.
+fn decimal(value: f64) -> Option<(i128, u32)> {
+    let text = value.to_string();
+    let (int, fraction) = text.split_once('.').unwrap_or((&text, ""));
+    let digits = format!("{}{}", int, fraction).parse::<i128>().ok()?;
+    Some((digits, fraction.len() as u32))
+}
+
+/// For values with more digits than a decimal holds.
+fn is_near_multiple(value: f64, multiple: f64) -> bool {
+    let quotient = value / multiple;
+    (quotient - quotient.round()).abs() <= 1e-9 * quotient.abs().max(1.0)
+}
+
 #[derive(Clone)]
 pub struct PromptOption<N> {
     multiple_of: Option<N>,
@@ -20,10 +101,21 @@ pub struct PromptOption<N> {
     minimum: Option<N>,
     maximum: Option<N>,
     enumeration: Vec<Option<N>>,
+    /// The range implied by the `format`, and its name.
+    format: Option<(N, N, String)>,
 }
 
 impl From<NumberType> for PromptOption<f64> {
     fn from(value: NumberType) -> Self {
+        let format = match value.format {
+            VariantOrUnknownOrEmpty::Item(NumberFormat::Float) => {
+                Some((f32::MIN as f64, f32::MAX as f64, "float".to_owned()))
+            }
+            VariantOrUnknownOrEmpty::Item(NumberFormat::Double) => {
+                Some((f64::MIN, f64::MAX, "double".to_owned()))
+            }
+            _ => None,
+        };
         Self {
             multiple_of: value.multiple_of,
             exclusive_minimum: value.exclusive_minimum,
@@ -31,28 +123,69 @@ impl From<NumberType> for PromptOption<f64> {
             minimum: value.minimum,
             maximum: value.maximum,
             enumeration: value.enumeration,
+            format,
         }
     }
 }
 
-impl From<IntegerType> for PromptOption<i64> {
+impl From<IntegerType> for PromptOption<i128> {
     fn from(value: IntegerType) -> Self {
+        let name = match &value.format {
+            VariantOrUnknownOrEmpty::Item(IntegerFormat::Int32) => "int32",
+            VariantOrUnknownOrEmpty::Item(IntegerFormat::Int64) => "int64",
+            VariantOrUnknownOrEmpty::Unknown(x) => x.as_str(),
+            VariantOrUnknownOrEmpty::Empty => "",
+        };
+        let range = match name {
+            "int8" => Some((i8::MIN as i128, i8::MAX as i128)),
+            "int16" => Some((i16::MIN as i128, i16::MAX as i128)),
+            "int32" => Some((i32::MIN as i128, i32::MAX as i128)),
+            "int64" => Some((i64::MIN as i128, i64::MAX as i128)),
+            "uint8" => Some((0, u8::MAX as i128)),
+            "uint16" => Some((0, u16::MAX as i128)),
+            "uint32" => Some((0, u32::MAX as i128)),
+            "uint" | "uint64" => Some((0, u64::MAX as i128)),
+            _ => None,
+        };
         Self {
-            multiple_of: value.multiple_of,
+            multiple_of: value.multiple_of.map(i128::from),
             exclusive_minimum: value.exclusive_minimum,
             exclusive_maximum: value.exclusive_maximum,
-            minimum: value.minimum,
-            maximum: value.maximum,
-            enumeration: value.enumeration,
+            minimum: value.minimum.map(i128::from),
+            maximum: value.maximum.map(i128::from),
+            enumeration: value
+                .enumeration
+                .into_iter()
+                .map(|x| x.map(i128::from))
+                .collect(),
+            format: range.map(|(min, max)| (min, max, name.to_owned())),
         }
     }
 }
 
-fn is_multiple_of<N>(value: N, multiple_of: N) -> bool
-where
-    N: Num,
-{
-    value.rem(multiple_of) == N::zero()
+impl<N: Numeric> PromptOption<N> {
+    /// The `minimum` and whether it is exclusive.
+    pub fn minimum(&self) -> Option<(N, bool)> {
+        self.minimum.clone().map(|x| (x, self.exclusive_minimum))
+    }
+
+    /// The `maximum` and whether it is exclusive.
+    pub fn maximum(&self) -> Option<(N, bool)> {
+        self.maximum.clone().map(|x| (x, self.exclusive_maximum))
+    }
+
+    pub fn multiple_of(&self) -> Option<N> {
+        self.multiple_of.clone()
+    }
+
+    pub fn enumeration(&self) -> impl Iterator<Item = &N> {
+        self.enumeration.iter().flatten()
+    }
+
+    /// The range implied by the `format`, and its name.
+    pub fn format(&self) -> Option<&(N, N, String)> {
+        self.format.as_ref()
+    }
 }
 
 fn is_minimum<N>(value: N, minimum: N, exclusive: bool) -> bool
@@ -79,12 +212,60 @@ where
 
 impl<N> Validator<String> for PromptOption<N>
 where
-    N: Num + PartialOrd + Clone + FromStr + ToString,
+    N: Numeric,
 {
     fn validate(&self, value: &String) -> Result<(), String> {
-        let value = value
-            .parse::<N>()
-            .map_err(|_| "Value is not a number".to_string())?;
+        // `inf` and `NaN` parse as floats, but are not JSON numbers.
+        let finite = value
+            .trim_start_matches(['+', '-'])
+            .starts_with(|x: char| x.is_ascii_digit());
+        // Integers with more digits than `i128` are still integers, out of any range.
+        let digits = value
+            .trim_start_matches(['+', '-'])
+            .chars()
+            .all(|x| x.is_ascii_digit());
+        let value = value.parse::<N>().ok().filter(|_| finite).ok_or_else(|| {
+            match (N::INTEGER, digits && finite, &self.format) {
+                (true, true, Some((min, max, name))) => format!(
+                    "Value must be between {} and {} (format: {})",
+                    min.to_string(),
+                    max.to_string(),
+                    name
+                ),
+                (true, true, None) => "Value is out of the range of integers (format)".to_string(),
+                (true, false, _) => "Value is not an integer (type)".to_string(),
+                (false, _, _) => "Value is not a number (type)".to_string(),
+            }
+        })?;
+
+        if let Some((min, max, name)) = &self.format {
+            if (value < *min || value > *max) && !N::INTEGER {
+                return Err(format!("Value is out of the range of {} (format)", name));
+            }
+            if value < *min || value > *max {
+                return Err(format!(
+                    "Value must be between {} and {} (format: {})",
+                    min.to_string(),
+                    max.to_string(),
+                    name
+                ));
+            }
+        }
+        // Without a format, integers are still bounded by `i64` and `u64`, past which they are rounded.
+        if self.format.is_none() && !value.is_exact() {
+            return Err("Value is out of the range of integers (format)".to_string());
+        }
+
+        if !self.enumeration.is_empty() && !self.enumeration.contains(&Some(value.clone())) {
+            let options = self
+                .enumeration
+                .iter()
+                .flatten()
+                .map(|x| x.to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
+            return Err(format!("Value must be one of {} (enum)", options));
+        }
 
         let range = match (self.minimum.clone(), self.maximum.clone()) {
             (Some(min), Some(max)) => {
@@ -94,7 +275,7 @@ where
                     Ok(())
                 } else {
                     Err(format!(
-                        "Value must be between {} and {}",
+                        "Value must be between {} and {} (minimum, maximum)",
                         min.to_string(),
                         max.to_string()
                     ))
@@ -105,7 +286,7 @@ where
                     Ok(())
                 } else {
                     Err(format!(
-                        "Value must be greater than or equal to {}",
+                        "Value must be greater than or equal to {} (minimum)",
                         min.to_string()
                     ))
                 }
@@ -115,7 +296,7 @@ where
                     Ok(())
                 } else {
                     Err(format!(
-                        "Value must be less than or equal to {}",
+                        "Value must be less than or equal to {} (maximum)",
                         max.to_string()
                     ))
                 }
@@ -124,11 +305,11 @@ where
         };
 
         let multiple = self.multiple_of.clone().map_or(Ok(()), |multiple| {
-            if is_multiple_of(value.clone(), multiple.clone()) {
+            if value.is_multiple_of(&multiple) {
                 Ok(())
             } else {
                 Err(format!(
-                    "Value must be a multiple of {}",
+                    "Value must be a multiple of {} (multipleOf)",
                     multiple.to_string()
                 ))
             }
@@ -139,14 +320,36 @@ where
 }
 
 pub struct Number {
-    original: promptuity::prompts::Number,
+    original: Input,
     enumeration: Option<Enumeration<Value>>,
+    integer: bool,
+    parse: fn(&str) -> Option<Value>,
+}
+
+/// Adapts a [`NumberFormatter`] to the text input the number is typed in.
+struct Formatter(Box<dyn NumberFormatter>);
+
+impl InputFormatter for Formatter {
+    fn err_required(&self) -> String {
+        self.0.err_required()
+    }
+}
+
+/// Integers are read exactly, even where a `number` is expected.
+fn parse<N: Numeric>(text: &str) -> Option<Value> {
+    if let Ok(value) = text.parse::<i64>() {
+        return Some(json!(value));
+    }
+    if let Ok(value) = text.parse::<u64>() {
+        return Some(json!(value));
+    }
+    text.parse::<N>().ok().map(|x| x.to_json())
 }
 
 impl Number {
     pub fn new<T>(message: String, option: PromptOption<T>) -> Self
     where
-        T: Num + PartialOrd + Clone + Default + FromStr + ToString + Serialize + 'static,
+        T: Numeric,
     {
         let enumeration = if option.enumeration.is_empty() {
             None
@@ -158,23 +361,25 @@ impl Number {
                 .flatten()
                 .map(|value| {
                     let label = value.to_string();
-                    SelectOption::new(label, json!(value))
+                    SelectOption::new(label, value.to_json())
                 })
                 .collect();
 
             Some(Enumeration::new(message.clone(), options))
         };
-        let mut original = promptuity::prompts::Number::new(message.clone());
+        let mut original = Input::new(message.clone());
         original.with_validator(option.clone());
 
         Self {
             original,
             enumeration,
+            integer: T::INTEGER,
+            parse: parse::<T>,
         }
     }
 
     pub fn with_formatter(&mut self, formatter: impl NumberFormatter + 'static) -> &mut Self {
-        self.original.with_formatter(formatter);
+        self.original.with_formatter(Formatter(Box::new(formatter)));
         self
     }
 
@@ -187,6 +392,36 @@ impl Number {
         self.original.with_placeholder(placeholder);
         self
     }
+
+    pub fn with_default(&mut self, value: &serde_json::Number) -> &mut Self {
+        if let Some(enumeration) = &mut self.enumeration {
+            enumeration.with_default(|x| x.as_number() == Some(value));
+        }
+        self.original.with_default(value);
+        self
+    }
+
+    /// `<Up>` and `<Down>` step the value by one.
+    fn step(&mut self, by: i8) {
+        let text = self.original.submit();
+        let value = if text.is_empty() {
+            Some(json!(0))
+        } else {
+            (self.parse)(&text)
+        };
+        let stepped = match value {
+            Some(Value::Number(n)) => match (n.as_i64(), n.as_u64(), n.as_f64()) {
+                (Some(x), _, _) => x.checked_add(by as i64).map(|x| x.to_string()),
+                (_, Some(x), _) => x.checked_add_signed(by as i64).map(|x| x.to_string()),
+                (_, _, Some(x)) => Some((x + by as f64).to_string()),
+                _ => None,
+            },
+            _ => None,
+        };
+        if let Some(stepped) = stepped {
+            self.original.with_default(stepped);
+        }
+    }
 }
 
 impl Prompt for Number {
@@ -205,7 +440,29 @@ impl Prompt for Number {
             return enumeration.handle(code, modifiers);
         }
 
-        self.original.handle(code, modifiers)
+        match (code, modifiers) {
+            (KeyCode::Up, _) => {
+                self.step(1);
+                PromptState::Active
+            }
+            (KeyCode::Down, _) => {
+                self.step(-1);
+                PromptState::Active
+            }
+            (KeyCode::Char(c), KeyModifiers::NONE | KeyModifiers::SHIFT) => {
+                let allowed = match c {
+                    '0'..='9' | '-' | '+' => true,
+                    '.' | 'e' | 'E' => !self.integer,
+                    _ => false,
+                };
+                if allowed {
+                    self.original.handle(code, modifiers)
+                } else {
+                    PromptState::Active
+                }
+            }
+            _ => self.original.handle(code, modifiers),
+        }
     }
 
     fn submit(&mut self) -> Self::Output {
@@ -213,7 +470,7 @@ impl Prompt for Number {
             return enumeration.submit();
         }
 
-        json!(self.original.submit())
+        (self.parse)(&self.original.submit()).unwrap_or(Value::Null)
     }
 
     fn render(&mut self, state: &PromptState) -> Result<RenderPayload, String> {
@@ -232,3 +489,59 @@ impl Prompt for Number {
         self.original.validate()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn integer_multiples() {
+        assert!(12i128.is_multiple_of(&4));
+        assert!(!13i128.is_multiple_of(&4));
+        assert!((-12i128).is_multiple_of(&4));
+        assert!(12i128.is_multiple_of(&-4));
+        assert!(0i128.is_multiple_of(&7));
+        assert!(5i128.is_multiple_of(&0));
+        assert!(i128::MIN.is_multiple_of(&-1));
+        assert!((u64::MAX as i128).is_multiple_of(&5));
+    }
+
+    #[test]
+    fn float_multiples() {
+        assert!(0.07.is_multiple_of(&0.01));
+        assert!(0.3.is_multiple_of(&0.1));
+        assert!(!0.35.is_multiple_of(&0.1));
+        assert!((-1.5).is_multiple_of(&0.5));
+        assert!(10.0.is_multiple_of(&2.5));
+        assert!(!10.0.is_multiple_of(&3.0));
+        assert!(3.0.is_multiple_of(&0.0));
+        assert!(0.0.is_multiple_of(&0.25));
+        assert!(1e300.is_multiple_of(&1e299));
+        assert!(!1e300.is_multiple_of(&3e299));
+        assert!(!f64::NAN.is_multiple_of(&1.0));
+        assert!(!f64::INFINITY.is_multiple_of(&1.0));
+    }
+
+    #[test]
+    fn integers_to_json() {
+        assert_eq!(i128::from(i64::MIN).to_json(), json!(i64::MIN));
+        assert_eq!(i128::from(u64::MAX).to_json(), json!(u64::MAX));
+        assert!((i128::from(u64::MAX) + 1).to_json().is_f64());
+        assert_eq!(0.5.to_json(), json!(0.5));
+    }
+
+    #[test]
+    fn integers_without_format() {
+        let option = PromptOption::<i128>::from(IntegerType::default());
+        assert!(option.validate(&u64::MAX.to_string()).is_ok());
+        assert!(option.validate(&i64::MIN.to_string()).is_ok());
+        let large = format!("1{}", "0".repeat(40));
+        for value in ["18446744073709551616", "-9223372036854775809", &large] {
+            assert_eq!(
+                option.validate(&value.to_owned()).unwrap_err(),
+                "Value is out of the range of integers (format)",
+                "{value}"
+            );
+        }
+    }
+}
@@ -2,19 +2,22 @@ use std::collections::VecDeque;
 
 use crossterm::style::Color;
 use indexmap::IndexMap;
-use openapiv3::{AdditionalProperties, ObjectType, OpenAPI, ReferenceOr, Schema};
+use openapiv3::{AdditionalProperties, ObjectType, OpenAPI, ReferenceOr, Schema, StringType};
 use promptuity::{
     event::{KeyCode, KeyModifiers},
     style::Styled,
-    Prompt, PromptBody, PromptState, RenderPayload,
+    Prompt, PromptBody, PromptState, RenderPayload, Validator,
 };
-use serde_json::{json, Value};
+use serde_json::{json, Map, Value};
 
-use crate::schema::reference::ReferenceOrExt;
+use crate::{
+    schema::{error::SchemaError, reference::ReferenceOrExt},
+    validate,
+};
 
 use super::utils::{fmt_body, fmt_input};
 
-use super::prompt_builder;
+use super::{is_answered, is_nested, prompt_builder, string, string::StringPrompt, PromptConfig};
 
 pub trait ObjectFormatter {
     fn fmt_prompt(&self, submitted: String, input: String) -> String;
@@ -31,13 +34,13 @@ impl ObjectFormatter for DefaultObjectFormatter {
 
     fn fmt_submitted_item(&self, key: String, value: Option<Value>) -> String {
         match value {
-            Some(value) => format!("{}: {}\n", key, value),
+            Some(value) => format!("{}: {}\n", key, fmt_value(&value)),
             None => format!("{}: undefined\n", key),
         }
     }
 
     fn fmt_input(&self, key: String, payload: RenderPayload, is_required: bool) -> String {
-        let prompt_input = fmt_input(&payload.input);
+        let prompt_input = fmt_input(&payload.input, payload.placeholder.as_deref());
         let prompt_body = fmt_body(&payload.body);
         let required = if !is_required { "?" } else { "" };
         let prompt_hint = Styled::new(payload.hint.map(|x| format!("{}\n", x)).unwrap_or_default())
@@ -50,7 +53,7 @@ impl ObjectFormatter for DefaultObjectFormatter {
     fn fmt_submit(&self, value: IndexMap<String, Value>) -> String {
         let value = value
             .iter()
-            .map(|(k, v)| format!("{}: {}", k, v))
+            .map(|(k, v)| format!("{}: {}", k, fmt_value(v)))
             .collect::<Vec<_>>()
             .join("\n");
 
@@ -58,12 +61,21 @@ impl ObjectFormatter for DefaultObjectFormatter {
     }
 }
 
-struct PromptOption {
+/// `null` is styled apart from the other values, so it is not taken for the string "null".
+fn fmt_value(value: &Value) -> String {
+    match value {
+        Value::Null => Styled::new("null").fg(Color::Magenta).italic().to_string(),
+        value => value.to_string(),
+    }
+}
+
+pub struct PromptOption {
     properties: IndexMap<String, ReferenceOr<Box<Schema>>>,
     required: Vec<String>,
-    _additional_properties: Option<AdditionalProperties>,
-    _min_properties: Option<usize>,
-    _max_properties: Option<usize>,
+    additional_properties: Option<AdditionalProperties>,
+    property_names: Option<StringType>,
+    min_properties: Option<usize>,
+    max_properties: Option<usize>,
 }
 
 impl PromptOption {
@@ -71,15 +83,91 @@ impl PromptOption {
         Self {
             properties: schema.properties,
             required: schema.required,
-            _additional_properties: schema.additional_properties,
-            _min_properties: schema.min_properties,
-            _max_properties: schema.max_properties,
+            additional_properties: schema.additional_properties,
+            property_names: None,
+            min_properties: schema.min_properties,
+            max_properties: schema.max_properties,
         }
     }
 
+    /// The constraints of the keys besides `properties`, from `x-propertyNames`.
+    pub fn with_property_names(&mut self, names: Option<StringType>) -> &mut Self {
+        self.property_names = names;
+        self
+    }
+
     fn has_required(&self, key: String) -> bool {
         self.required.contains(&key)
     }
+
+    /// Whether keys besides `properties` are entered. Objects without
+    /// `properties` are free-form maps unless `additionalProperties` is false.
+    fn is_map(&self) -> bool {
+        match &self.additional_properties {
+            Some(AdditionalProperties::Any(any)) => *any,
+            Some(AdditionalProperties::Schema(_)) => true,
+            None => self.properties.is_empty(),
+        }
+    }
+
+    /// Checks everything but `minProperties`, which the prompts may still satisfy.
+    pub fn validate_keys(&self, value: &Map<String, Value>) -> Result<(), String> {
+        for key in value.keys().filter(|x| !self.properties.contains_key(*x)) {
+            if let Some(AdditionalProperties::Any(false)) = self.additional_properties {
+                return Err(format!(
+                    "Property '{}' is not allowed (additionalProperties)",
+                    key
+                ));
+            }
+            if let Some(names) = &self.property_names {
+                let option = string::PromptOption::from(names.clone());
+                option
+                    .validate(key)
+                    .map_err(|err| format!("Property name '{}': {}", key, err))?;
+            }
+        }
+
+        if let Some(max) = self.max_properties {
+            if value.len() > max {
+                return Err(format!(
+                    "Object must have at most {} properties (maxProperties)",
+                    max
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Validator<Map<String, Value>> for PromptOption {
+    fn validate(&self, value: &Map<String, Value>) -> Result<(), String> {
+        if let Some(min) = self.min_properties {
+            if value.len() < min {
+                return Err(format!(
+                    "Object must have at least {} properties (minProperties)",
+                    min
+                ));
+            }
+        }
+
+        self.validate_keys(value)
+    }
+}
+
+/// `x-propertyNames` of the schema. OpenAPI 3.0 has no `propertyNames`,
+/// so it is read from the extension when the schema sets it.
+pub fn property_names(schema: &Schema) -> Option<StringType> {
+    let names = schema.schema_data.extensions.get("x-propertyNames")?;
+    serde_json::from_value(names.clone()).ok()
+}
+
+/// What the current prompt answers.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Phase {
+    Property,
+    Key,
+    Value,
 }
 
 pub struct Object {
@@ -88,7 +176,13 @@ pub struct Object {
     api: OpenAPI,
     formatter: Box<dyn ObjectFormatter>,
     value: IndexMap<String, Option<Value>>,
+    default: IndexMap<String, Value>,
+    config: PromptConfig,
+    deprecated: Vec<String>,
     prompts: VecDeque<(String, Box<dyn Prompt<Output = Value>>)>,
+    /// Keys prompted so far, in order, to go back through.
+    answered: Vec<String>,
+    phase: Phase,
     current_prompt: Option<(String, Box<dyn Prompt<Output = Value>>)>,
 }
 
@@ -100,7 +194,12 @@ impl Object {
             api: api.clone(),
             formatter: Box::new(DefaultObjectFormatter),
             value: IndexMap::new(),
+            default: IndexMap::new(),
+            config: PromptConfig::default(),
+            deprecated: Vec::new(),
             prompts: VecDeque::new(),
+            answered: Vec::new(),
+            phase: Phase::Property,
             current_prompt: None,
         }
     }
@@ -110,6 +209,11 @@ impl Object {
         self
     }
 
+    pub fn with_config(&mut self, config: PromptConfig) -> &mut Self {
+        self.config = config;
+        self
+    }
+
     pub fn with_value(&mut self, value: IndexMap<String, Value>) -> &mut Self {
         self.value = value
             .into_iter()
@@ -118,98 +222,420 @@ impl Object {
         self
     }
 
+    pub fn with_default(&mut self, value: IndexMap<String, Value>) -> &mut Self {
+        self.default = value;
+        self
+    }
+
+    pub fn with_property_names(&mut self, names: Option<StringType>) -> &mut Self {
+        self.option.with_property_names(names);
+        self
+    }
+
+    /// Moves to the next property, or to a new key of a map once the properties are done.
     fn next_prompt(&mut self) -> Result<bool, promptuity::Error> {
-        let mut prompt = self.prompts.pop_front();
-        if let Some((_, prompt)) = &mut prompt {
+        if let Some((key, mut prompt)) = self.prompts.pop_front() {
             prompt.setup()?;
+            self.phase = Phase::Property;
+            self.current_prompt = Some((key, prompt));
+            return Ok(true);
         }
 
-        self.current_prompt = prompt;
-        Ok(self.current_prompt.is_some())
-    }
+        let count = self.get_value().len();
+        if !self.option.is_map() || self.option.max_properties.is_some_and(|x| count >= x) {
+            self.current_prompt = None;
+            return Ok(false);
+        }
 
-    fn get_value(&self) -> IndexMap<String, Value> {
-        self.value
-            .clone()
-            .into_iter()
-            .filter_map(|(k, v)| v.map(|x| (k.clone(), x.clone())))
-            .collect::<IndexMap<_, _>>()
+        let names = self.option.property_names.clone().unwrap_or_default();
+        let mut prompt = StringPrompt::new("key".to_owned(), names.into());
+        prompt.with_hint("<Esc> to finish");
+        prompt.setup()?;
+        self.phase = Phase::Key;
+        self.current_prompt = Some((String::new(), Box::new(prompt)));
+        Ok(true)
     }
-}
 
-impl Prompt for Object {
-    type Output = Value;
+    fn value_prompt(
+        &self,
+        key: &str,
+        default: Option<Value>,
+    ) -> Result<Box<dyn Prompt<Output = Value>>, SchemaError> {
+        Ok(match &self.option.additional_properties {
+            Some(AdditionalProperties::Schema(schema)) => {
+                let schema = schema.item(&self.api)?;
+                prompt_builder(
+                    &self.api,
+                    schema,
+                    key.to_owned(),
+                    schema.schema_data.description.clone(),
+                    None,
+                    default,
+                    &self.config,
+                )
+            }
+            _ => {
+                let mut prompt = StringPrompt::new(key.to_owned(), StringType::default().into());
+                prompt.with_hint("JSON or text");
+                if let Some(default) = default {
+                    prompt.with_default(match default {
+                        Value::String(x) => x,
+                        x => x.to_string(),
+                    });
+                }
+                Box::new(prompt)
+            }
+        })
+    }
 
-    fn setup(&mut self) -> Result<(), promptuity::Error> {
-        let properties = self.option.properties.clone();
-        let properties = properties
-            .into_iter()
-            .filter(|(k, _)| !self.value.contains_key(k));
+    /// Values of free-form maps are typed as JSON and fall back to a string, as `-f` does.
+    fn parse_value(&self, key: &str, value: Value) -> Value {
+        let free = !self.option.properties.contains_key(key)
+            && !matches!(
+                self.option.additional_properties,
+                Some(AdditionalProperties::Schema(_))
+            );
+        match value {
+            Value::String(x) if free => x.parse::<Value>().unwrap_or(Value::String(x)),
+            value => value,
+        }
+    }
 
-        for (key, schema) in properties {
-            let schema = schema.unbox();
-            let schema = schema
-                .item(&self.api)
-                .map_err(|x| promptuity::Error::Config(x.to_string()))?;
+    /// The schema of a property or of an entry of the map.
+    fn schema(&self, key: &str) -> Result<Option<Schema>, SchemaError> {
+        let schema = match (
+            self.option.properties.get(key),
+            &self.option.additional_properties,
+        ) {
+            (Some(property), _) => property.clone().unbox(),
+            (None, Some(AdditionalProperties::Schema(schema))) => *schema.clone(),
+            (None, _) => return Ok(None),
+        };
+        Ok(Some(schema.item(&self.api)?.clone()))
+    }
 
-            let prompt = prompt_builder(
+    /// Prompts the key again, pre-filled with its current answer.
+    fn edit_prompt(&self, key: &str) -> Result<Box<dyn Prompt<Output = Value>>, SchemaError> {
+        let default = self
+            .value
+            .get(key)
+            .cloned()
+            .flatten()
+            .or_else(|| self.default.get(key).cloned());
+        match (self.option.properties.contains_key(key), self.schema(key)?) {
+            (true, Some(schema)) => Ok(prompt_builder(
                 &self.api,
-                schema,
-                key.clone(),
+                &schema,
+                key.to_owned(),
                 schema.schema_data.description.clone(),
                 None,
-            );
-            self.prompts.push_back((key.clone(), prompt));
+                default,
+                &self.config,
+            )),
+            _ => self.value_prompt(key, default),
         }
+    }
 
-        if !self.next_prompt()? {
-            return Err(promptuity::Error::Config("No prompt found".to_string()));
+    /// Whether the current prompt is an object or an array, which goes back through its own fields first.
+    fn is_current_nested(&self) -> bool {
+        match &self.current_prompt {
+            Some((key, _)) if self.phase != Phase::Key => {
+                matches!(self.schema(key), Ok(Some(schema)) if is_nested(&schema))
+            }
+            _ => false,
         }
+    }
 
-        Ok(())
+    /// Goes back to the previous key, or tells the parent to when this is the first one.
+    fn back(&mut self) -> PromptState {
+        let Some(key) = self.answered.pop() else {
+            self.config.back.set(true);
+            return PromptState::Submit;
+        };
+
+        // The current key is asked again afterwards, while a new key of the map is dropped.
+        if let (Some((current, _)), Phase::Property) = (&self.current_prompt, self.phase) {
+            let current = current.clone();
+            match self.edit_prompt(&current) {
+                Ok(prompt) => self.prompts.push_front((current, prompt)),
+                Err(err) => return PromptState::Fatal(err.to_string()),
+            }
+        }
+        let mut prompt = match self.edit_prompt(&key) {
+            Ok(prompt) => prompt,
+            Err(err) => return PromptState::Fatal(err.to_string()),
+        };
+        if let Err(err) = prompt.setup() {
+            return PromptState::Fatal(err.to_string());
+        }
+        self.phase = Phase::Property;
+        self.current_prompt = Some((key, prompt));
+        PromptState::Active
     }
 
-    fn handle(&mut self, code: KeyCode, modifiers: KeyModifiers) -> PromptState {
+    fn is_optional(&self, key: &str) -> bool {
+        self.option.properties.contains_key(key) && !self.option.has_required(key.to_owned())
+    }
+
+    /// Whether `<C-s>` has optional properties left to skip.
+    fn has_optional(&self) -> bool {
+        let current = match (&self.current_prompt, self.phase) {
+            (Some((key, _)), Phase::Property) => self.is_optional(key),
+            _ => false,
+        };
+        current || self.prompts.iter().any(|(key, _)| self.is_optional(key))
+    }
+
+    /// Leaves the current and the remaining optional properties undefined.
+    /// They are still reached by going back. A new key of a map finishes it.
+    fn skip_optional(&mut self) -> PromptState {
+        if self.phase == Phase::Key {
+            return self.handle_key(KeyCode::Esc, KeyModifiers::NONE);
+        }
+
+        let current = match (&self.current_prompt, self.phase) {
+            (Some((key, _)), Phase::Property) if self.is_optional(key) => Some(key.clone()),
+            _ => None,
+        };
+        let (skipped, prompts) = std::mem::take(&mut self.prompts)
+            .into_iter()
+            .partition::<VecDeque<_>, _>(|(key, _)| self.is_optional(key));
+        self.prompts = prompts;
+
+        let skipped = skipped.into_iter().map(|(key, _)| key);
+        for key in current.clone().into_iter().chain(skipped) {
+            self.value.insert(key.clone(), None);
+            self.answered.push(key);
+        }
+
+        match current {
+            Some(_) => self.advance(),
+            None => PromptState::Active,
+        }
+    }
+
+    fn advance(&mut self) -> PromptState {
+        match self.next_prompt() {
+            Ok(false) => PromptState::Submit,
+            Ok(true) => PromptState::Active,
+            Err(err) => PromptState::Fatal(err.to_string()),
+        }
+    }
+
+    fn handle_key(&mut self, code: KeyCode, modifiers: KeyModifiers) -> PromptState {
         if let (KeyCode::Char('c'), KeyModifiers::CONTROL) = (code, modifiers) {
             return PromptState::Cancel;
         }
 
-        let (key, prompt) = self.current_prompt.as_mut().unwrap();
+        if code == KeyCode::BackTab {
+            if self.is_current_nested() {
+                let (_, prompt) = self.current_prompt.as_mut().unwrap();
+                let state = prompt.handle(code, modifiers);
+                if !self.config.back.take() {
+                    return state;
+                }
+            }
+            return self.back();
+        }
+
+        // A nested object skips its own optional properties.
+        if let (KeyCode::Char('s'), KeyModifiers::CONTROL) = (code, modifiers) {
+            if !self.is_current_nested() {
+                return self.skip_optional();
+            }
+        }
+
+        // Every property is given or skipped, so Enter only confirms the object.
+        let Some((key, prompt)) = self.current_prompt.as_mut() else {
+            return match code {
+                KeyCode::Enter => PromptState::Submit,
+                _ => PromptState::Active,
+            };
+        };
 
         let state = prompt.handle(code, modifiers);
-        match state {
-            PromptState::Submit => {
+        match (self.phase, state) {
+            (Phase::Key, PromptState::Submit) => {
+                if let Err(err) = prompt.validate() {
+                    return PromptState::Error(err);
+                }
+                let key = match prompt.submit() {
+                    Value::String(key) => key,
+                    key => key.to_string(),
+                };
+                if self.value.contains_key(&key) || self.option.properties.contains_key(&key) {
+                    return PromptState::Error(format!("'{}' is already defined", key));
+                }
+
+                let prompt = match self.value_prompt(&key, self.default.get(&key).cloned()) {
+                    Ok(prompt) => prompt,
+                    Err(err) => return PromptState::Fatal(err.to_string()),
+                };
+                self.current_prompt = Some((key, prompt));
+                self.phase = Phase::Value;
+                match self.current_prompt.as_mut().unwrap().1.setup() {
+                    Ok(()) => PromptState::Active,
+                    Err(err) => PromptState::Fatal(err.to_string()),
+                }
+            }
+            (Phase::Key, PromptState::Cancel) => {
+                let value = self.get_value().into_iter().collect::<Map<_, _>>();
+                if let Err(err) = self.option.validate(&value) {
+                    return PromptState::Error(err);
+                }
+                self.current_prompt = None;
+                PromptState::Submit
+            }
+            (_, PromptState::Submit) => {
                 let is_valid = prompt.validate();
                 if let Err(err) = is_valid {
                     return PromptState::Error(err);
                 }
 
+                let key = key.clone();
                 let value = prompt.submit();
+                let value = self.parse_value(&key, value);
                 self.value.insert(key.clone(), Some(value));
+                self.answered.push(key);
 
-                let init = self.next_prompt();
-                match init {
-                    Ok(false) => PromptState::Submit,
-                    Ok(true) => PromptState::Active,
-                    Err(err) => PromptState::Fatal(err.to_string()),
-                }
+                self.advance()
             }
-            PromptState::Cancel => {
+            // A new entry of a map is dropped and the key is asked again.
+            (Phase::Value, PromptState::Cancel) => self.advance(),
+            (Phase::Property, PromptState::Cancel) => {
                 if self.option.has_required(key.clone()) {
                     PromptState::Error(format!("{} is required field", key))
                 } else {
                     self.value.insert(key.clone(), None);
+                    self.answered.push(key.clone());
+
+                    self.advance()
+                }
+            }
+            (_, state) => state,
+        }
+    }
+
+    /// Whether `<Esc>` is used by the object: it finishes a map or drops its new entry,
+    /// and skips an optional property once a field is answered. A nested prompt tells its own.
+    fn uses_esc(&self) -> bool {
+        match self.phase {
+            Phase::Key | Phase::Value => true,
+            Phase::Property => {
+                (self.current_prompt.is_some() && !self.value.is_empty())
+                    || (self.is_current_nested() && self.config.esc.get())
+            }
+        }
+    }
+
+    fn get_value(&self) -> IndexMap<String, Value> {
+        self.value
+            .clone()
+            .into_iter()
+            .filter_map(|(k, v)| v.map(|x| (k.clone(), x.clone())))
+            .collect::<IndexMap<_, _>>()
+    }
+}
+
+impl Prompt for Object {
+    type Output = Value;
+
+    fn setup(&mut self) -> Result<(), promptuity::Error> {
+        let violations = validate::validate_properties(
+            &self.api,
+            &self.option.properties,
+            self.value
+                .iter()
+                .filter_map(|(k, v)| v.as_ref().map(|v| (k, v))),
+            &self.message,
+        )
+        .map_err(|x| promptuity::Error::Config(x.to_string()))?;
+        let given = self.get_value().into_iter().collect::<Map<_, _>>();
+        if let Err(err) = self.option.validate_keys(&given) {
+            return Err(promptuity::Error::Config(format!(
+                "{}: {}",
+                self.message, err
+            )));
+        }
+        if !violations.is_empty() {
+            let violations = violations
+                .iter()
+                .map(|x| x.to_string())
+                .collect::<Vec<_>>()
+                .join("\n");
+            return Err(promptuity::Error::Config(violations));
+        }
 
-                    let init = self.next_prompt();
-                    match init {
-                        Ok(false) => PromptState::Submit,
-                        Ok(true) => PromptState::Active,
-                        Err(err) => PromptState::Fatal(err.to_string()),
+        let properties = self.option.properties.clone();
+        for (key, schema) in properties {
+            let schema = schema.unbox();
+            let schema = schema
+                .item(&self.api)
+                .map_err(|x| promptuity::Error::Config(x.to_string()))?;
+
+            let value = self.value.get(&key).cloned().flatten();
+            match &value {
+                Some(value) => {
+                    let answered = is_answered(&self.api, schema, value, &self.config)
+                        .map_err(|x| promptuity::Error::Config(x.to_string()))?;
+                    if answered {
+                        continue;
                     }
                 }
+                None if self
+                    .config
+                    .skips(schema, self.option.has_required(key.clone())) =>
+                {
+                    continue
+                }
+                None => {}
+            }
+            if schema.schema_data.deprecated {
+                self.deprecated.push(key.clone());
             }
-            _ => state,
+
+            let prompt = prompt_builder(
+                &self.api,
+                schema,
+                key.clone(),
+                schema.schema_data.description.clone(),
+                value,
+                self.default.get(&key).cloned(),
+                &self.config,
+            );
+            self.prompts.push_back((key.clone(), prompt));
         }
+
+        // Entries of a map answered before are offered again, to be kept or edited.
+        if self.option.is_map() {
+            let keys = self
+                .default
+                .keys()
+                .filter(|x| !self.option.properties.contains_key(*x))
+                .filter(|x| !self.value.contains_key(*x))
+                .cloned()
+                .collect::<Vec<_>>();
+            for key in keys {
+                let prompt = self
+                    .value_prompt(&key, self.default.get(&key).cloned())
+                    .map_err(|x| promptuity::Error::Config(x.to_string()))?;
+                self.prompts.push_back((key, prompt));
+            }
+        }
+
+        self.next_prompt()?;
+        self.config.esc.set(self.uses_esc());
+
+        Ok(())
+    }
+
+    fn handle(&mut self, code: KeyCode, modifiers: KeyModifiers) -> PromptState {
+        let state = self.handle_key(code, modifiers);
+        let esc = self.uses_esc();
+        self.config
+            .esc
+            .set(esc && matches!(state, PromptState::Active | PromptState::Error(_)));
+        state
     }
 
     fn submit(&mut self) -> Self::Output {
@@ -224,18 +650,38 @@ impl Prompt for Object {
             PromptState::Submit => Ok(RenderPayload::new(self.message.clone(), None, None)
                 .body(PromptBody::Raw(self.formatter.fmt_submit(self.get_value())))),
             _ => {
-                let (key, prompt) = self.current_prompt.as_mut().unwrap();
-
-                let prompt_payload = prompt.render(state)?;
-                let input = self.formatter.fmt_input(
-                    key.clone(),
-                    prompt_payload,
-                    self.option.has_required(key.clone()),
-                );
-
+                let skippable = !self.is_current_nested() && self.has_optional();
+                let input = match self.current_prompt.as_mut() {
+                    Some((key, prompt)) => {
+                        let mut prompt_payload = prompt.render(state)?;
+                        if skippable {
+                            prompt_payload.hint = Some(match prompt_payload.hint {
+                                Some(hint) => format!("{}, <C-s> to skip optional fields", hint),
+                                None => "<C-s> to skip optional fields".to_owned(),
+                            });
+                        }
+                        let label = match self.phase {
+                            Phase::Key => "(new key)".to_owned(),
+                            _ if self.deprecated.contains(key) => format!("{} (deprecated)", key),
+                            _ => key.clone(),
+                        };
+                        let required =
+                            self.phase != Phase::Property || self.option.has_required(key.clone());
+                        self.formatter.fmt_input(label, prompt_payload, required)
+                    }
+                    None => Styled::new("<Enter> to continue\n")
+                        .fg(Color::DarkGrey)
+                        .to_string(),
+                };
+
+                let editing = match (&self.current_prompt, self.phase) {
+                    (Some((key, _)), Phase::Property) => Some(key.clone()),
+                    _ => None,
+                };
                 let submitted = self
                     .value
                     .iter()
+                    .filter(|(key, _)| Some(*key) != editing.as_ref())
                     .map(|(key, value)| {
                         self.formatter
                             .fmt_submitted_item(key.clone(), value.clone())
@@ -251,3 +697,65 @@ impl Prompt for Object {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prompts::optional_prompt_builder;
+
+    fn optional_prompt(schema: Value) -> Box<dyn Prompt<Output = Option<Value>>> {
+        let schema = serde_json::from_value::<Schema>(schema).unwrap();
+        let mut prompt = optional_prompt_builder(
+            &OpenAPI::default(),
+            &schema,
+            "map".to_owned(),
+            None,
+            None,
+            None,
+            &PromptConfig::default(),
+        );
+        prompt.setup().unwrap();
+        prompt
+    }
+
+    fn type_text(prompt: &mut dyn Prompt<Output = Option<Value>>, text: &str) {
+        for c in text.chars() {
+            prompt.handle(KeyCode::Char(c), KeyModifiers::NONE);
+        }
+        assert_eq!(
+            prompt.handle(KeyCode::Enter, KeyModifiers::NONE),
+            PromptState::Active
+        );
+    }
+
+    #[test]
+    fn esc_finishes_optional_map() {
+        let map = json!({"type": "object", "additionalProperties": {"type": "string"}});
+        let mut prompt = optional_prompt(map.clone());
+        type_text(&mut *prompt, "a");
+        type_text(&mut *prompt, "1");
+        assert_eq!(
+            prompt.handle(KeyCode::Esc, KeyModifiers::NONE),
+            PromptState::Submit
+        );
+        assert_eq!(prompt.submit(), Some(json!({"a": "1"})));
+
+        let mut prompt = optional_prompt(map);
+        assert_eq!(
+            prompt.handle(KeyCode::Esc, KeyModifiers::NONE),
+            PromptState::Submit
+        );
+        assert_eq!(prompt.submit(), Some(json!({})));
+    }
+
+    #[test]
+    fn esc_skips_untouched_object() {
+        let object = json!({"type": "object", "properties": {"a": {"type": "string"}}});
+        let mut prompt = optional_prompt(object);
+        assert_eq!(
+            prompt.handle(KeyCode::Esc, KeyModifiers::NONE),
+            PromptState::Submit
+        );
+        assert_eq!(prompt.submit(), None);
+    }
+}
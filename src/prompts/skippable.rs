@@ -1,31 +1,36 @@
+use std::{cell::Cell, rc::Rc};
+
 use promptuity::event::{KeyCode, KeyModifiers};
 use promptuity::{Prompt, PromptInput, PromptState, RenderPayload};
 
-pub struct Skippable<P>
-where
-    P: Prompt,
-{
-    prompt: P,
+pub struct Skippable<O> {
+    prompt: Box<dyn Prompt<Output = O>>,
     skip: bool,
+    /// Set while the prompt uses `<Esc>` itself.
+    esc: Rc<Cell<bool>>,
 }
 
-impl<P> Skippable<P>
-where
-    P: Prompt,
-{
-    pub fn new(prompt: P) -> Self {
+impl<O> Skippable<O> {
+    pub fn new(prompt: impl Prompt<Output = O> + 'static) -> Self {
+        Self::from_boxed(Box::new(prompt))
+    }
+
+    pub fn from_boxed(prompt: Box<dyn Prompt<Output = O>>) -> Self {
         Self {
             prompt,
             skip: false,
+            esc: Rc::new(Cell::new(false)),
         }
     }
+
+    pub fn with_esc(&mut self, esc: Rc<Cell<bool>>) -> &mut Self {
+        self.esc = esc;
+        self
+    }
 }
 
-impl<P> Prompt for Skippable<P>
-where
-    P: Prompt,
-{
-    type Output = Option<P::Output>;
+impl<O> Prompt for Skippable<O> {
+    type Output = Option<O>;
 
     fn setup(&mut self) -> Result<(), promptuity::Error> {
         self.prompt.setup()
@@ -33,7 +38,7 @@ where
 
     fn handle(&mut self, code: KeyCode, modifiers: KeyModifiers) -> PromptState {
         match (code, modifiers) {
-            (KeyCode::Esc, _) => {
+            (KeyCode::Esc, _) if !self.esc.get() => {
                 self.skip = true;
                 PromptState::Submit
             }
@@ -59,6 +64,7 @@ where
                 } else {
                     prompt.input
                 })),
+            _ if self.esc.get() => Ok(prompt),
             _ => Ok(RenderPayload::new(
                 prompt.message.clone(),
                 prompt
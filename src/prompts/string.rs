@@ -1,17 +1,25 @@
+use chrono::Local;
 use openapiv3::{StringFormat, StringType, VariantOrUnknownOrEmpty};
 use promptuity::{
     event::{KeyCode, KeyModifiers},
     prompts::{Input, Password, SelectOption},
     Prompt, PromptState, RenderPayload, Validator,
 };
+use regex::Regex;
 use serde_json::Value;
 
-use super::enumeration::Enumeration;
+use crate::schema::{error::PatternError, pattern};
+
+use super::{
+    enumeration::Enumeration,
+    format::{self, Format},
+};
 
 #[derive(Clone)]
 pub struct PromptOption {
     format: VariantOrUnknownOrEmpty<StringFormat>,
-    pattern: Option<String>,
+    /// Compiled once, since it is checked on every submit.
+    pattern: Option<(String, Result<Regex, PatternError>)>,
     enumeration: Vec<Option<String>>,
     min_length: Option<usize>,
     max_length: Option<usize>,
@@ -21,7 +29,10 @@ impl From<StringType> for PromptOption {
     fn from(string_type: StringType) -> Self {
         Self {
             format: string_type.format,
-            pattern: string_type.pattern,
+            pattern: string_type.pattern.map(|x| {
+                let regex = pattern::compile(&x);
+                (x, regex)
+            }),
             enumeration: string_type.enumeration,
             min_length: string_type.min_length,
             max_length: string_type.max_length,
@@ -29,28 +40,80 @@ impl From<StringType> for PromptOption {
     }
 }
 
+impl PromptOption {
+    pub fn format(&self) -> Option<Format> {
+        Format::from_schema(&self.format)
+    }
+
+    pub fn pattern(&self) -> Option<&str> {
+        self.pattern.as_ref().map(|(x, _)| x.as_str())
+    }
+
+    /// Whether values are checked against the pattern, which is not the case when
+    /// it uses a syntax the `regex` crate lacks.
+    pub fn checks_pattern(&self) -> bool {
+        !matches!(self.pattern, Some((_, Err(PatternError::Unsupported(_)))))
+    }
+
+    pub fn enumeration(&self) -> impl Iterator<Item = &String> {
+        self.enumeration.iter().flatten()
+    }
+
+    pub fn min_length(&self) -> Option<usize> {
+        self.min_length
+    }
+
+    pub fn max_length(&self) -> Option<usize> {
+        self.max_length
+    }
+}
+
 impl Validator<String> for PromptOption {
     fn validate(&self, value: &String) -> Result<(), String> {
-        if let Some(pattern) = &self.pattern {
-            let re = regex::Regex::new(pattern).map_err(|err| err.to_string())?;
-            if !re.is_match(value) {
-                return Err(format!("Value does not match pattern: {}", pattern));
+        if !self.enumeration.is_empty() && !self.enumeration.contains(&Some(value.clone())) {
+            let options = self
+                .enumeration
+                .iter()
+                .flatten()
+                .cloned()
+                .collect::<Vec<_>>()
+                .join(", ");
+            return Err(format!("Value must be one of {} (enum)", options));
+        }
+
+        if let Some(format) = Format::from_schema(&self.format) {
+            format.check(value)?;
+        }
+
+        match &self.pattern {
+            Some((pattern, Ok(regex))) if !regex.is_match(value) => {
+                return Err(format!(
+                    "Value does not match pattern: {} (pattern)",
+                    pattern
+                ));
+            }
+            Some((_, Err(PatternError::Unsupported(_)))) => {}
+            Some((pattern, Err(err))) => {
+                return Err(format!("Invalid pattern: {}: {}", pattern, err));
             }
+            _ => {}
         }
 
+        // Lengths are counted in characters, not bytes.
+        let length = value.chars().count();
         if let Some(min_length) = self.min_length {
-            if value.len() < min_length {
+            if length < min_length {
                 return Err(format!(
-                    "Value is too short. Minimum length is {}",
+                    "Value is too short. Minimum length is {} (minLength)",
                     min_length
                 ));
             }
         }
 
         if let Some(max_length) = self.max_length {
-            if value.len() > max_length {
+            if length > max_length {
                 return Err(format!(
-                    "Value is too long. Maximum length is {}",
+                    "Value is too long. Maximum length is {} (maxLength)",
                     max_length
                 ));
             }
@@ -64,6 +127,7 @@ pub struct StringPrompt {
     original: Input,
     enumeration: Option<Enumeration<Value>>,
     password: Option<Password>,
+    format: Option<Format>,
 }
 
 impl StringPrompt {
@@ -102,6 +166,7 @@ impl StringPrompt {
             original,
             enumeration,
             password,
+            format: Format::from_schema(&option.format),
         }
     }
 
@@ -109,6 +174,20 @@ impl StringPrompt {
         self.original.with_hint(hint);
         self
     }
+
+    pub fn with_placeholder(&mut self, placeholder: impl std::fmt::Display) -> &mut Self {
+        self.original.with_placeholder(placeholder);
+        self
+    }
+
+    pub fn with_default(&mut self, value: impl std::fmt::Display) -> &mut Self {
+        let value = value.to_string();
+        if let Some(enumeration) = &mut self.enumeration {
+            enumeration.with_default(|x| x.as_str() == Some(&value));
+        }
+        self.original.with_default(value);
+        self
+    }
 }
 
 impl Prompt for StringPrompt {
@@ -131,6 +210,23 @@ impl Prompt for StringPrompt {
             return password.handle(code, modifiers);
         }
 
+        match (self.format, code, modifiers) {
+            // `<Tab>` shows what a word such as `today` stands for, and `<Enter>` submits it.
+            (Some(format), KeyCode::Tab | KeyCode::Enter, _) => {
+                if let Some(value) = format.expand(&self.original.submit(), Local::now()) {
+                    self.original.with_default(value);
+                }
+                if code == KeyCode::Tab {
+                    return PromptState::Active;
+                }
+            }
+            (Some(Format::Uuid), KeyCode::Char('g'), KeyModifiers::CONTROL) => {
+                self.original.with_default(format::uuid());
+                return PromptState::Active;
+            }
+            _ => {}
+        }
+
         self.original.handle(code, modifiers)
     }
 
@@ -152,7 +248,17 @@ impl Prompt for StringPrompt {
             return password.render(state);
         }
 
-        self.original.render(state)
+        let payload = self.original.render(state)?;
+        match self.format.and_then(|x| x.hint()) {
+            Some(hint) => Ok(RenderPayload {
+                hint: Some(match &payload.hint {
+                    Some(x) => format!("{}, {}", x, hint),
+                    None => hint.to_owned(),
+                }),
+                ..payload
+            }),
+            None => Ok(payload),
+        }
     }
 
     fn validate(&self) -> Result<(), String> {
@@ -1,4 +1,9 @@
-use promptuity::{style::Styled, PromptBody, PromptInput};
+use serde_json::Value;
+
+use promptuity::{
+    style::{Color, Styled},
+    InputCursor, PromptBody, PromptInput,
+};
 
 pub fn fmt_body(body: &PromptBody) -> String {
     match body {
@@ -7,8 +12,18 @@ pub fn fmt_body(body: &PromptBody) -> String {
     }
 }
 
-pub fn fmt_input(input: &PromptInput) -> String {
+/// Formats the input of a nested prompt, with the placeholder while it is empty.
+pub fn fmt_input(input: &PromptInput, placeholder: Option<&str>) -> String {
     match input {
+        PromptInput::Cursor(c) if c.value().is_empty() && placeholder.is_some() => {
+            let placeholder = InputCursor::new(placeholder.unwrap_or_default().to_owned(), 0);
+            let (_, cursor, right) = placeholder.split();
+            format!(
+                "{}{}",
+                Styled::new(cursor).rev(),
+                Styled::new(right).fg(Color::DarkGrey)
+            )
+        }
         PromptInput::Raw(s) => s.to_owned(),
         PromptInput::Cursor(c) => {
             let (left, cursor, right) = c.split();
@@ -17,3 +32,25 @@ pub fn fmt_input(input: &PromptInput) -> String {
         PromptInput::None => String::new(),
     }
 }
+
+/// The value with `escape` applied to its strings, if any.
+pub fn escape_strings(value: &Value, escape: Option<fn(&str) -> String>) -> Value {
+    let Some(escape) = escape else {
+        return value.clone();
+    };
+    match value {
+        Value::String(x) => Value::String(escape(x)),
+        Value::Array(items) => Value::Array(
+            items
+                .iter()
+                .map(|x| escape_strings(x, Some(escape)))
+                .collect(),
+        ),
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(k, v)| (k.clone(), escape_strings(v, Some(escape))))
+                .collect(),
+        ),
+        _ => value.clone(),
+    }
+}
@@ -1,6 +1,7 @@
 use std::fmt::Display;
 
 use anyhow::Result;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use url::Url;
 
@@ -31,7 +32,7 @@ impl Display for ParamsValue {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RequestInit {
     pub method: String,
     pub base: String,
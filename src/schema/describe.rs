@@ -0,0 +1,288 @@
+use openapiv3::{
+    AdditionalProperties, OpenAPI, ReferenceOr, Schema, SchemaKind, Type, VariantOrUnknownOrEmpty,
+};
+use serde::Serialize;
+
+use crate::schema::{error::SchemaError, reference::ReferenceOrExt};
+
+/// A schema as a tree of named nodes, for printing.
+#[derive(Debug, Clone)]
+pub struct SchemaNode {
+    pub name: String,
+    pub required: bool,
+    pub type_name: String,
+    pub constraints: Vec<String>,
+    pub description: Option<String>,
+    pub children: Vec<SchemaNode>,
+}
+
+impl SchemaNode {
+    pub fn new(api: &OpenAPI, name: String, schema: &Schema) -> Result<Self, SchemaError> {
+        build(api, name, schema, false, &mut Vec::new())
+    }
+
+    pub fn from_ref(
+        api: &OpenAPI,
+        name: String,
+        schema: &ReferenceOr<Schema>,
+    ) -> Result<Self, SchemaError> {
+        build_ref(api, name, schema, false, &mut Vec::new())
+    }
+}
+
+/// `string(date-time)`, `integer(int64)`, `array`, ...
+pub fn type_name(schema: &Schema) -> String {
+    match &schema.schema_kind {
+        SchemaKind::Type(Type::String(x)) => with_format("string", &x.format),
+        SchemaKind::Type(Type::Number(x)) => with_format("number", &x.format),
+        SchemaKind::Type(Type::Integer(x)) => with_format("integer", &x.format),
+        SchemaKind::Type(Type::Boolean(_)) => "boolean".to_owned(),
+        SchemaKind::Type(Type::Object(_)) => "object".to_owned(),
+        SchemaKind::Type(Type::Array(_)) => "array".to_owned(),
+        SchemaKind::OneOf { .. } => "oneOf".to_owned(),
+        SchemaKind::AllOf { .. } => "allOf".to_owned(),
+        SchemaKind::AnyOf { .. } => "anyOf".to_owned(),
+        SchemaKind::Not { .. } => "not".to_owned(),
+        SchemaKind::Any(x) => x.typ.clone().unwrap_or("any".to_owned()),
+    }
+}
+
+fn with_format<T: Serialize>(name: &str, format: &VariantOrUnknownOrEmpty<T>) -> String {
+    match serde_json::to_value(format) {
+        Ok(serde_json::Value::String(format)) => format!("{}({})", name, format),
+        _ => name.to_owned(),
+    }
+}
+
+/// The validation keywords of the schema, e.g. `minLength: 2`.
+pub fn constraints(schema: &Schema) -> Vec<String> {
+    let mut result = Vec::new();
+    let mut push = |name: &str, value: Option<String>| {
+        if let Some(value) = value {
+            result.push(format!("{}: {}", name, value));
+        }
+    };
+    let enumeration = |values: Vec<String>| (!values.is_empty()).then(|| values.join(" | "));
+    let minimum = |exclusive: bool| {
+        if exclusive {
+            "exclusiveMinimum"
+        } else {
+            "minimum"
+        }
+    };
+    let maximum = |exclusive: bool| {
+        if exclusive {
+            "exclusiveMaximum"
+        } else {
+            "maximum"
+        }
+    };
+
+    match &schema.schema_kind {
+        SchemaKind::Type(Type::String(x)) => {
+            push("minLength", x.min_length.map(|x| x.to_string()));
+            push("maxLength", x.max_length.map(|x| x.to_string()));
+            push("pattern", x.pattern.clone());
+            push(
+                "enum",
+                enumeration(x.enumeration.iter().flatten().cloned().collect()),
+            );
+        }
+        SchemaKind::Type(Type::Number(x)) => {
+            push(
+                minimum(x.exclusive_minimum),
+                x.minimum.map(|x| x.to_string()),
+            );
+            push(
+                maximum(x.exclusive_maximum),
+                x.maximum.map(|x| x.to_string()),
+            );
+            push("multipleOf", x.multiple_of.map(|x| x.to_string()));
+            push(
+                "enum",
+                enumeration(
+                    x.enumeration
+                        .iter()
+                        .flatten()
+                        .map(|x| x.to_string())
+                        .collect(),
+                ),
+            );
+        }
+        SchemaKind::Type(Type::Integer(x)) => {
+            push(
+                minimum(x.exclusive_minimum),
+                x.minimum.map(|x| x.to_string()),
+            );
+            push(
+                maximum(x.exclusive_maximum),
+                x.maximum.map(|x| x.to_string()),
+            );
+            push("multipleOf", x.multiple_of.map(|x| x.to_string()));
+            push(
+                "enum",
+                enumeration(
+                    x.enumeration
+                        .iter()
+                        .flatten()
+                        .map(|x| x.to_string())
+                        .collect(),
+                ),
+            );
+        }
+        SchemaKind::Type(Type::Array(x)) => {
+            push("minItems", x.min_items.map(|x| x.to_string()));
+            push("maxItems", x.max_items.map(|x| x.to_string()));
+            push("uniqueItems", x.unique_items.then(|| "true".to_owned()));
+        }
+        SchemaKind::Type(Type::Object(x)) => {
+            push("minProperties", x.min_properties.map(|x| x.to_string()));
+            push("maxProperties", x.max_properties.map(|x| x.to_string()));
+        }
+        _ => {}
+    }
+
+    let data = &schema.schema_data;
+    push("default", data.default.as_ref().map(|x| x.to_string()));
+    for (flag, name) in [
+        (data.nullable, "nullable"),
+        (data.read_only, "readOnly"),
+        (data.write_only, "writeOnly"),
+        (data.deprecated, "deprecated"),
+    ] {
+        if flag {
+            result.push(name.to_owned());
+        }
+    }
+
+    result
+}
+
+fn build_ref(
+    api: &OpenAPI,
+    name: String,
+    schema: &ReferenceOr<Schema>,
+    required: bool,
+    stack: &mut Vec<String>,
+) -> Result<SchemaNode, SchemaError> {
+    match schema {
+        ReferenceOr::Reference { reference } => {
+            let ref_name = reference.rsplit('/').next().unwrap_or(reference).to_owned();
+            let item = schema.item(api)?;
+            // Recursive schemas are printed once and referenced by name below.
+            if stack.contains(reference) {
+                return Ok(SchemaNode {
+                    name,
+                    required,
+                    type_name: format!("{} (recursive)", ref_name),
+                    constraints: Vec::new(),
+                    description: None,
+                    children: Vec::new(),
+                });
+            }
+
+            stack.push(reference.clone());
+            let mut node = build(api, name, item, required, stack)?;
+            stack.pop();
+            node.type_name = format!("{} ({})", node.type_name, ref_name);
+            Ok(node)
+        }
+        ReferenceOr::Item(item) => build(api, name, item, required, stack),
+    }
+}
+
+fn build(
+    api: &OpenAPI,
+    name: String,
+    schema: &Schema,
+    required: bool,
+    stack: &mut Vec<String>,
+) -> Result<SchemaNode, SchemaError> {
+    let mut children = Vec::new();
+    match &schema.schema_kind {
+        SchemaKind::Type(Type::Object(object)) => {
+            for (key, property) in &object.properties {
+                let property = property.clone().unbox();
+                children.push(build_ref(
+                    api,
+                    key.clone(),
+                    &property,
+                    object.required.contains(key),
+                    stack,
+                )?);
+            }
+            if let Some(AdditionalProperties::Schema(additional)) = &object.additional_properties {
+                children.push(build_ref(api, "*".to_owned(), additional, false, stack)?);
+            }
+        }
+        SchemaKind::Type(Type::Array(array)) => {
+            if let Some(items) = &array.items {
+                let items = items.clone().unbox();
+                children.push(build_ref(api, "[]".to_owned(), &items, false, stack)?);
+            }
+        }
+        SchemaKind::OneOf { one_of: schemas }
+        | SchemaKind::AllOf { all_of: schemas }
+        | SchemaKind::AnyOf { any_of: schemas } => {
+            for (idx, schema) in schemas.iter().enumerate() {
+                children.push(build_ref(api, format!("#{}", idx), schema, false, stack)?);
+            }
+        }
+        SchemaKind::Not { not } => {
+            children.push(build_ref(api, "not".to_owned(), not, false, stack)?);
+        }
+        _ => {}
+    }
+
+    Ok(SchemaNode {
+        name,
+        required,
+        type_name: type_name(schema),
+        constraints: constraints(schema),
+        description: schema.schema_data.description.clone(),
+        children,
+    })
+}
+
+/// Paths of the deprecated properties which are set in the value.
+pub fn deprecated_fields(
+    api: &OpenAPI,
+    schema: &Schema,
+    value: &serde_json::Value,
+    path: &str,
+) -> Result<Vec<String>, SchemaError> {
+    let mut result = Vec::new();
+    match (&schema.schema_kind, value) {
+        (SchemaKind::Type(Type::Object(object)), serde_json::Value::Object(map)) => {
+            for (key, value) in map {
+                let Some(property) = object.properties.get(key) else {
+                    continue;
+                };
+                let property = property.clone().unbox();
+                let property = property.item(api)?;
+                let path = format!("{}.{}", path, key);
+                if property.schema_data.deprecated {
+                    result.push(path.clone());
+                }
+                result.extend(deprecated_fields(api, property, value, &path)?);
+            }
+        }
+        (SchemaKind::Type(Type::Array(array)), serde_json::Value::Array(items)) => {
+            if let Some(item) = &array.items {
+                let item = item.clone().unbox();
+                let item = item.item(api)?;
+                for (idx, value) in items.iter().enumerate() {
+                    result.extend(deprecated_fields(
+                        api,
+                        item,
+                        value,
+                        &format!("{}[{}]", path, idx),
+                    )?);
+                }
+            }
+        }
+        _ => {}
+    }
+
+    Ok(result)
+}
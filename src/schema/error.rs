@@ -8,4 +8,32 @@ pub enum SchemaError {
     UnsupportedExternalReference,
     #[error("Unsupported schema type")]
     UnsupportedSchema,
+    #[error("Invalid pattern of {path}: {pattern}\n{message}")]
+    InvalidPattern {
+        path: String,
+        pattern: String,
+        message: String,
+    },
+    #[error("Failed to generate a value matching {pattern} with {} characters", lengths(.min, .max))]
+    UnsatisfiablePattern {
+        pattern: String,
+        min: usize,
+        max: Option<usize>,
+    },
+}
+
+fn lengths(min: &usize, max: &Option<usize>) -> String {
+    match max {
+        Some(max) => format!("{} to {}", min, max),
+        None => format!("at least {}", min),
+    }
+}
+
+#[derive(Error, Debug, Clone)]
+pub enum PatternError {
+    /// Valid in ECMA-262, but lacking in the `regex` crate, so the pattern is not checked.
+    #[error("{0} are not supported")]
+    Unsupported(&'static str),
+    #[error("{0}")]
+    Invalid(String),
 }
@@ -1,3 +1,8 @@
+pub mod describe;
 pub mod error;
+pub mod operation;
+pub mod path;
+pub mod pattern;
 pub mod read;
 pub mod reference;
+pub mod skeleton;
@@ -0,0 +1,82 @@
+use openapiv3::{OpenAPI, Operation, Parameter, ParameterData};
+
+use crate::schema::{error::SchemaError, reference::ReferenceOrExt};
+
+/// An operation with the path and the method it is defined on.
+#[derive(Debug, Clone, Default)]
+pub struct OperationItem {
+    pub method: String,
+    pub path: String,
+    pub operation: Operation,
+}
+
+impl OperationItem {
+    /// Collects every operation in the schema, in the order of definition.
+    pub fn all(api: &OpenAPI) -> Result<Vec<Self>, SchemaError> {
+        let mut items = Vec::new();
+        for (path, path_item) in api.paths.iter() {
+            let path_item = path_item.item(api)?;
+            for (method, operation) in path_item.iter() {
+                items.push(Self {
+                    method: method.to_uppercase(),
+                    path: path.clone(),
+                    operation: operation.clone(),
+                });
+            }
+        }
+
+        Ok(items)
+    }
+
+    pub fn find_by_id(api: &OpenAPI, operation_id: &str) -> Result<Option<Self>, SchemaError> {
+        Ok(Self::all(api)?
+            .into_iter()
+            .find(|x| x.operation.operation_id.as_deref() == Some(operation_id)))
+    }
+
+    pub fn find(api: &OpenAPI, method: &str, path: &str) -> Result<Option<Self>, SchemaError> {
+        Ok(Self::all(api)?
+            .into_iter()
+            .find(|x| x.method.eq_ignore_ascii_case(method) && x.path == path))
+    }
+
+    /// `METHOD path — summary`
+    pub fn label(&self) -> String {
+        match &self.operation.summary {
+            Some(summary) => format!("{} {} — {}", self.method, self.path, summary),
+            None => format!("{} {}", self.method, self.path),
+        }
+    }
+
+    /// Sorts the operations by their first tag, in the order of the tags of the schema.
+    /// Untagged operations come last.
+    pub fn group_by_tag(api: &OpenAPI, items: &mut [Self]) {
+        let order = |item: &Self| match item.operation.tags.first() {
+            Some(tag) => match api.tags.iter().position(|x| &x.name == tag) {
+                Some(idx) => (0, idx, tag.clone()),
+                None => (1, 0, tag.clone()),
+            },
+            None => (2, 0, String::new()),
+        };
+        items.sort_by_key(order);
+    }
+
+    /// Resolves the parameters of the operation with their location.
+    pub fn parameters<'a>(
+        &'a self,
+        api: &'a OpenAPI,
+    ) -> Result<Vec<(&'static str, &'a ParameterData)>, SchemaError> {
+        self.operation
+            .parameters
+            .iter()
+            .map(|x| {
+                Ok(match x.item(api)? {
+                    Parameter::Query { parameter_data, .. } => ("query", parameter_data),
+                    Parameter::Header { parameter_data, .. } => ("header", parameter_data),
+                    Parameter::Path { parameter_data, .. } => ("path", parameter_data),
+                    Parameter::Cookie { parameter_data, .. } => ("cookie", parameter_data),
+                })
+            })
+            .collect()
+    }
+}
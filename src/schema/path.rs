@@ -0,0 +1,266 @@
+use std::sync::LazyLock;
+
+use indexmap::IndexMap;
+use openapiv3::OpenAPI;
+use percent_encoding::percent_decode_str;
+use regex::Regex;
+use url::Url;
+
+static PLACEHOLDER: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\{([^}]+)\}").unwrap());
+
+/// A concrete path matched to a path template of the schema.
+#[derive(Debug, Clone)]
+pub struct PathMatch {
+    pub template: String,
+    pub params: IndexMap<String, String>,
+}
+
+/// Splits a URL or a path with a query string into the path and the query parameters.
+pub fn split_url(input: &str) -> (String, Vec<(String, String)>) {
+    let url = Url::parse(input)
+        .ok()
+        .filter(|x| x.has_host())
+        .or_else(|| Url::parse("http://localhost").ok()?.join(input).ok());
+
+    match url {
+        Some(url) => (
+            url.path().to_owned(),
+            url.query_pairs()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+        ),
+        None => (input.to_owned(), Vec::new()),
+    }
+}
+
+/// The path component of the servers, e.g. `/v1` for `https://api.example.com/v1`.
+pub fn base_paths<'a>(urls: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    let mut paths = urls
+        .into_iter()
+        .filter_map(|x| match Url::parse(x) {
+            Ok(url) => Some(url.path().to_owned()),
+            Err(_) if x.starts_with('/') => Some(x.to_owned()),
+            Err(_) => None,
+        })
+        .map(|x| x.trim_end_matches('/').to_owned())
+        .filter(|x| !x.is_empty())
+        .collect::<Vec<_>>();
+    paths.sort_by_key(|x| std::cmp::Reverse(x.len()));
+    paths.dedup();
+    paths
+}
+
+/// Finds the path template which matches the concrete path, with or without
+/// one of `base_paths` in front. Templates with more literal segments win,
+/// so `/users/me` is preferred over `/users/{id}`.
+pub fn match_path(api: &OpenAPI, path: &str, base_paths: &[String]) -> Option<PathMatch> {
+    if api.paths.paths.contains_key(path) {
+        return Some(PathMatch {
+            template: path.to_owned(),
+            params: IndexMap::new(),
+        });
+    }
+
+    let mut candidates = vec![path];
+    candidates.extend(
+        base_paths
+            .iter()
+            .filter_map(|x| path.strip_prefix(x.as_str()))
+            .filter(|x| x.starts_with('/')),
+    );
+
+    let mut best: Option<(usize, PathMatch)> = None;
+    for candidate in candidates {
+        for template in api.paths.paths.keys() {
+            let Some((score, params)) = match_template(template, candidate) else {
+                continue;
+            };
+            if best.as_ref().is_none_or(|(x, _)| score > *x) {
+                best = Some((
+                    score,
+                    PathMatch {
+                        template: template.clone(),
+                        params,
+                    },
+                ));
+            }
+        }
+    }
+
+    best.map(|(_, x)| x)
+}
+
+/// Returns the number of literal segments and the parameters when the path matches.
+fn match_template(template: &str, path: &str) -> Option<(usize, IndexMap<String, String>)> {
+    let templates = template
+        .trim_end_matches('/')
+        .split('/')
+        .collect::<Vec<_>>();
+    let segments = path.trim_end_matches('/').split('/').collect::<Vec<_>>();
+    if templates.len() != segments.len() {
+        return None;
+    }
+
+    let mut literals = 0;
+    let mut params = IndexMap::new();
+    for (template, segment) in templates.into_iter().zip(segments) {
+        if !template.contains('{') {
+            if template != segment {
+                return None;
+            }
+            literals += 1;
+            continue;
+        }
+
+        let mut pieces = Vec::new();
+        let mut last = 0;
+        for caps in PLACEHOLDER.captures_iter(template) {
+            let whole = caps.get(0)?;
+            pieces.push(Piece::Literal(&template[last..whole.start()]));
+            pieces.push(Piece::Param(caps.get(1)?.as_str()));
+            last = whole.end();
+        }
+        pieces.push(Piece::Literal(&template[last..]));
+
+        let mut values = Vec::new();
+        if !match_pieces(&pieces, segment, &mut values) {
+            return None;
+        }
+        for (name, value) in values {
+            let value = percent_decode_str(value).decode_utf8_lossy().to_string();
+            params.insert(name.to_owned(), value);
+        }
+    }
+
+    Some((literals, params))
+}
+
+/// A part of a path segment of a template, e.g. `{name}` and `.json` of `{name}.json`.
+enum Piece<'a> {
+    Literal(&'a str),
+    Param(&'a str),
+}
+
+/// Matches the segment to the pieces. A parameter takes as few characters as it can,
+/// and at least one, so `{name}.{ext}` splits `a.b.json` at the first dot.
+fn match_pieces<'a>(
+    pieces: &[Piece<'a>],
+    segment: &'a str,
+    values: &mut Vec<(&'a str, &'a str)>,
+) -> bool {
+    let Some((piece, rest)) = pieces.split_first() else {
+        return segment.is_empty();
+    };
+    match piece {
+        Piece::Literal(literal) => segment
+            .strip_prefix(literal)
+            .is_some_and(|segment| match_pieces(rest, segment, values)),
+        Piece::Param(name) => {
+            let ends = (1..=segment.len()).filter(|x| segment.is_char_boundary(*x));
+            for end in ends {
+                let len = values.len();
+                values.push((name, &segment[..end]));
+                if match_pieces(rest, &segment[end..], values) {
+                    return true;
+                }
+                values.truncate(len);
+            }
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params(pairs: &[(&str, &str)]) -> IndexMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn match_template_params() {
+        assert_eq!(
+            match_template("/users/{id}", "/users/42"),
+            Some((2, params(&[("id", "42")])))
+        );
+        assert_eq!(
+            match_template("/users/{id}/", "/users/42"),
+            Some((2, params(&[("id", "42")])))
+        );
+        assert_eq!(
+            match_template("/files/{name}.{ext}", "/files/a.b.json"),
+            Some((2, params(&[("name", "a"), ("ext", "b.json")])))
+        );
+        assert_eq!(
+            match_template("/users/{id}", "/users/a%20b"),
+            Some((2, params(&[("id", "a b")])))
+        );
+        assert_eq!(
+            match_template("/v{version}/users", "/v2/users"),
+            Some((2, params(&[("version", "2")])))
+        );
+        assert_eq!(
+            match_template("/{a}-{b}", "/日本-語-x"),
+            Some((1, params(&[("a", "日本"), ("b", "語-x")])))
+        );
+        assert_eq!(
+            match_template("/a.{b}", "/a.(.*)"),
+            Some((1, params(&[("b", "(.*)")])))
+        );
+    }
+
+    #[test]
+    fn match_template_mismatch() {
+        assert_eq!(match_template("/users/{id}", "/users"), None);
+        assert_eq!(match_template("/users/{id}", "/users/1/posts"), None);
+        assert_eq!(match_template("/users/{id}", "/groups/1"), None);
+        assert_eq!(match_template("/users/{id}", "/users/"), None);
+        assert_eq!(match_template("/files/{name}.json", "/files/a.xml"), None);
+        assert_eq!(match_template("/a.b", "/axb"), None);
+        assert_eq!(match_template("/{a}{b}", "/x"), None);
+        assert_eq!(match_template("/files/{name}.json", "/files/.json"), None);
+    }
+
+    #[test]
+    fn match_path_prefers_literals() {
+        let api: OpenAPI = serde_json::from_value(serde_json::json!({
+            "openapi": "3.0.0",
+            "info": {"title": "", "version": ""},
+            "paths": {"/users/{id}": {}, "/users/me": {}, "/users/{id}/posts": {}}
+        }))
+        .unwrap();
+        let template = |path: &str| match_path(&api, path, &base_paths(["/v1/"]));
+
+        assert_eq!(template("/users/me").unwrap().template, "/users/me");
+        assert_eq!(template("/users/me/").unwrap().template, "/users/me");
+        let found = template("/v1/users/7/posts").unwrap();
+        assert_eq!(found.template, "/users/{id}/posts");
+        assert_eq!(found.params, params(&[("id", "7")]));
+        assert!(template("/v2/users/7").is_none());
+    }
+
+    #[test]
+    fn split_urls() {
+        assert_eq!(
+            split_url("https://api.example.com/v1/users?a=1&b=x%20y"),
+            (
+                "/v1/users".to_owned(),
+                vec![("a".into(), "1".into()), ("b".into(), "x y".into())]
+            )
+        );
+        assert_eq!(split_url("/users/1"), ("/users/1".to_owned(), vec![]));
+        assert_eq!(
+            base_paths([
+                "https://a.example.com/v1/",
+                "/v1",
+                "https://b.example.com",
+                "/api/v2"
+            ]),
+            ["/api/v2", "/v1"]
+        );
+    }
+}
@@ -0,0 +1,381 @@
+use std::collections::HashSet;
+
+use openapiv3::{OpenAPI, ParameterSchemaOrContent, ReferenceOr, Schema, SchemaKind, Type};
+use regex::Regex;
+
+use crate::schema::{
+    error::{PatternError, SchemaError},
+    reference::ReferenceOrExt,
+};
+
+/// Compiles a `pattern`, which is an ECMA-262 regular expression, with the `regex` crate.
+/// The syntax they spell differently is translated first.
+pub fn compile(pattern: &str) -> Result<Regex, PatternError> {
+    Regex::new(&translate(pattern)?).map_err(|err| PatternError::Invalid(err.to_string()))
+}
+
+/// Rewrites the pattern in the syntax of the `regex` crate:
+/// - `\d`, `\w` and `\b` only match ASCII, like in JavaScript
+/// - `\uXXXX`, `\cX` and `\0` are spelled as `\x{...}`, and `\p{...}` is kept
+/// - `[^]` and `[]`, literal `[` in classes and braces which are not a quantifier
+///
+/// Backreferences and lookarounds are not supported by the `regex` crate.
+pub fn translate(pattern: &str) -> Result<String, PatternError> {
+    let invalid = |message: &str| PatternError::Invalid(message.to_owned());
+    let chars = pattern.chars().collect::<Vec<_>>();
+    let mut out = String::new();
+    let mut in_class = false;
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let rest = &chars[i + 1..];
+        match c {
+            '\\' => {
+                let Some(&next) = rest.first() else {
+                    return Err(invalid("Pattern ends with '\\'"));
+                };
+                i += 1;
+                match next {
+                    'd' => out.push_str("[0-9]"),
+                    'D' => out.push_str("[^0-9]"),
+                    'w' => out.push_str("[0-9A-Za-z_]"),
+                    'W' => out.push_str("[^0-9A-Za-z_]"),
+                    'b' if !in_class => out.push_str(r"(?-u:\b)"),
+                    'B' if !in_class => out.push_str(r"(?-u:\B)"),
+                    // A backspace in a class.
+                    'b' => out.push_str(r"\x08"),
+                    'u' if rest.get(1) == Some(&'{') => {
+                        let end = rest
+                            .iter()
+                            .position(|x| *x == '}')
+                            .ok_or_else(|| invalid("Unclosed '\\u{'"))?;
+                        let hex = rest[2..end].iter().collect::<String>();
+                        out.push_str(&format!(r"\x{{{}}}", hex));
+                        i += end;
+                    }
+                    'u' if rest.len() > 4 && rest[1..5].iter().all(|x| x.is_ascii_hexdigit()) => {
+                        let hex = rest[1..5].iter().collect::<String>();
+                        out.push_str(&format!(r"\x{{{}}}", hex));
+                        i += 4;
+                    }
+                    'c' if rest.get(1).is_some_and(|x| x.is_ascii_alphabetic()) => {
+                        let code = rest[1].to_ascii_uppercase() as u32 - 'A' as u32 + 1;
+                        out.push_str(&format!(r"\x{{{:02X}}}", code));
+                        i += 1;
+                    }
+                    // Unicode properties are spelled the same, and their braces are kept.
+                    'p' | 'P' if rest.get(1) == Some(&'{') => {
+                        let end = rest
+                            .iter()
+                            .position(|x| *x == '}')
+                            .ok_or_else(|| invalid("Unclosed '\\p{'"))?;
+                        out.push('\\');
+                        out.extend(&rest[..=end]);
+                        i += end;
+                    }
+                    '0' if !rest.get(1).is_some_and(|x| x.is_ascii_digit()) => {
+                        out.push_str(r"\x00")
+                    }
+                    '1'..='9' | 'k' if !in_class => {
+                        return Err(PatternError::Unsupported("Backreferences"))
+                    }
+                    '/' => out.push('/'),
+                    next => {
+                        out.push('\\');
+                        out.push(next);
+                    }
+                }
+            }
+            '(' if !in_class => {
+                let rest = rest.iter().collect::<String>();
+                if ["?=", "?!", "?<=", "?<!"]
+                    .iter()
+                    .any(|x| rest.starts_with(x))
+                {
+                    return Err(PatternError::Unsupported("Lookaround assertions"));
+                }
+                if rest.starts_with("?<") {
+                    out.push_str("(?P<");
+                    i += 2;
+                } else {
+                    out.push('(');
+                }
+            }
+            '[' if !in_class => match rest {
+                ['^', ']', ..] => {
+                    out.push_str(r"[\s\S]");
+                    i += 2;
+                }
+                [']', ..] => {
+                    out.push_str(r"[^\s\S]");
+                    i += 1;
+                }
+                ['^', ..] => {
+                    out.push_str("[^");
+                    in_class = true;
+                    i += 1;
+                }
+                _ => {
+                    out.push('[');
+                    in_class = true;
+                }
+            },
+            ']' if in_class => {
+                out.push(']');
+                in_class = false;
+            }
+            // Nested classes and set operations of the `regex` crate are literals in JavaScript.
+            '[' | '&' | '~' if in_class => {
+                out.push('\\');
+                out.push(c);
+            }
+            '-' if in_class && rest.first() == Some(&'-') => out.push_str(r"-\"),
+            '{' if !in_class => match quantifier(rest) {
+                Some(end) => {
+                    out.push('{');
+                    out.extend(&rest[..=end]);
+                    i += end + 1;
+                }
+                None => out.push_str(r"\{"),
+            },
+            '}' if !in_class => out.push_str(r"\}"),
+            c => out.push(c),
+        }
+        i += 1;
+    }
+
+    if in_class {
+        return Err(invalid("Unclosed character class"));
+    }
+    Ok(out)
+}
+
+/// The position of the closing brace of `{n}`, `{n,}` or `{n,m}`, given without the opening one.
+fn quantifier(rest: &[char]) -> Option<usize> {
+    let end = rest.iter().position(|x| *x == '}')?;
+    let body = rest[..end].iter().collect::<String>();
+    let mut parts = body.splitn(2, ',');
+    let min = parts.next().unwrap_or_default();
+    let max = parts.next();
+
+    let valid = !min.is_empty()
+        && min.chars().all(|x| x.is_ascii_digit())
+        && max.is_none_or(|x| x.chars().all(|x| x.is_ascii_digit()));
+    valid.then_some(end)
+}
+
+/// Compiles every `pattern` of the schema, so a broken one is reported before prompting.
+/// Patterns the `regex` crate does not support are left unchecked, and returned as warnings.
+pub fn check(api: &OpenAPI, schema: &Schema, path: &str) -> Result<Vec<String>, SchemaError> {
+    let mut warnings = Vec::new();
+    check_schema(api, schema, path, &mut HashSet::new(), &mut warnings)?;
+    Ok(warnings)
+}
+
+/// Checks the patterns of the schemas of parameters. Content parameters have none.
+pub fn check_parameter(
+    api: &OpenAPI,
+    format: &ParameterSchemaOrContent,
+    path: &str,
+) -> Result<Vec<String>, SchemaError> {
+    let mut warnings = Vec::new();
+    if let ParameterSchemaOrContent::Schema(schema) = format {
+        check_ref(api, schema, path, &mut HashSet::new(), &mut warnings)?;
+    }
+    Ok(warnings)
+}
+
+fn check_ref(
+    api: &OpenAPI,
+    schema: &ReferenceOr<Schema>,
+    path: &str,
+    visited: &mut HashSet<String>,
+    warnings: &mut Vec<String>,
+) -> Result<(), SchemaError> {
+    if let ReferenceOr::Reference { reference } = schema {
+        // Recursive schemas are checked once.
+        if !visited.insert(reference.clone()) {
+            return Ok(());
+        }
+    }
+    check_schema(api, schema.item(api)?, path, visited, warnings)
+}
+
+fn check_boxed(
+    api: &OpenAPI,
+    schema: &ReferenceOr<Box<Schema>>,
+    path: &str,
+    visited: &mut HashSet<String>,
+    warnings: &mut Vec<String>,
+) -> Result<(), SchemaError> {
+    check_ref(api, &schema.clone().unbox(), path, visited, warnings)
+}
+
+fn check_schema(
+    api: &OpenAPI,
+    schema: &Schema,
+    path: &str,
+    visited: &mut HashSet<String>,
+    warnings: &mut Vec<String>,
+) -> Result<(), SchemaError> {
+    match &schema.schema_kind {
+        SchemaKind::Type(Type::String(string)) => match string.pattern.as_deref().map(compile) {
+            Some(Err(err @ PatternError::Unsupported(_))) => {
+                warnings.push(format!("The pattern of {} is not checked: {}", path, err))
+            }
+            Some(Err(err)) => {
+                return Err(SchemaError::InvalidPattern {
+                    path: path.to_owned(),
+                    pattern: string.pattern.clone().unwrap_or_default(),
+                    message: err.to_string(),
+                })
+            }
+            _ => {}
+        },
+        SchemaKind::Type(Type::Object(object)) => {
+            for (key, property) in &object.properties {
+                let path = format!("{}.{}", path, key);
+                check_boxed(api, property, &path, visited, warnings)?;
+            }
+            if let Some(openapiv3::AdditionalProperties::Schema(schema)) =
+                &object.additional_properties
+            {
+                check_ref(api, schema, &format!("{}.*", path), visited, warnings)?;
+            }
+        }
+        SchemaKind::Type(Type::Array(array)) => {
+            if let Some(items) = &array.items {
+                check_boxed(api, items, &format!("{}[]", path), visited, warnings)?;
+            }
+        }
+        SchemaKind::OneOf { one_of: schemas }
+        | SchemaKind::AllOf { all_of: schemas }
+        | SchemaKind::AnyOf { any_of: schemas } => {
+            for schema in schemas {
+                check_ref(api, schema, path, visited, warnings)?;
+            }
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn translate_unicode_properties() {
+        assert_eq!(translate(r"^\p{L}+$").unwrap(), r"^\p{L}+$");
+        assert_eq!(translate(r"\P{Lu}").unwrap(), r"\P{Lu}");
+        assert_eq!(
+            translate(r"[\p{Script=Greek}]").unwrap(),
+            r"[\p{Script=Greek}]"
+        );
+        assert!(compile(r"^\p{L}+$").unwrap().is_match("日本語"));
+        assert!(!compile(r"^\P{Lu}$").unwrap().is_match("A"));
+        assert!(translate(r"\p{L").is_err());
+    }
+
+    #[test]
+    fn translate_ascii_classes() {
+        assert_eq!(
+            translate(r"\d\D\w\W").unwrap(),
+            "[0-9][^0-9][0-9A-Za-z_][^0-9A-Za-z_]"
+        );
+        assert!(!compile(r"^\d$").unwrap().is_match("٣"));
+        assert!(!compile(r"^\w$").unwrap().is_match("é"));
+        assert_eq!(translate(r"[\b]").unwrap(), r"[\x08]");
+    }
+
+    #[test]
+    fn translate_escapes() {
+        assert_eq!(translate(r"\u0041").unwrap(), r"\x{0041}");
+        assert_eq!(translate(r"\u{1F600}").unwrap(), r"\x{1F600}");
+        assert_eq!(translate(r"\cJ").unwrap(), r"\x{0A}");
+        assert_eq!(translate(r"\cj").unwrap(), r"\x{0A}");
+        assert_eq!(translate(r"\0").unwrap(), r"\x00");
+        assert_eq!(translate(r"a\/b").unwrap(), "a/b");
+        assert!(compile(r"^\cJ$").unwrap().is_match("\n"));
+    }
+
+    #[test]
+    fn translate_classes() {
+        assert_eq!(translate("[^]").unwrap(), r"[\s\S]");
+        assert_eq!(translate("[]").unwrap(), r"[^\s\S]");
+        assert_eq!(translate("[a[&~]").unwrap(), r"[a\[\&\~]");
+        assert!(compile("^[^]$").unwrap().is_match("\n"));
+        assert!(!compile("[]").unwrap().is_match("a"));
+        assert!(translate("[a").is_err());
+    }
+
+    #[test]
+    fn translate_braces() {
+        assert_eq!(translate("a{2}").unwrap(), "a{2}");
+        assert_eq!(translate("a{2,}b{1,3}").unwrap(), "a{2,}b{1,3}");
+        assert_eq!(translate("a{").unwrap(), r"a\{");
+        assert_eq!(translate("{a}").unwrap(), r"\{a\}");
+        assert_eq!(translate("a{,2}").unwrap(), r"a\{,2\}");
+        assert!(compile("^{x}$").unwrap().is_match("{x}"));
+    }
+
+    #[test]
+    fn translate_groups() {
+        assert_eq!(
+            translate("(?<year>[0-9]{4})").unwrap(),
+            "(?P<year>[0-9]{4})"
+        );
+        assert_eq!(translate("(?:a)").unwrap(), "(?:a)");
+    }
+
+    #[test]
+    fn translate_unsupported() {
+        for pattern in ["(?=a)", "(?!a)", "(?<=a)", "(?<!a)"] {
+            assert_eq!(
+                translate(pattern).unwrap_err().to_string(),
+                "Lookaround assertions are not supported"
+            );
+        }
+        assert!(translate(r"(a)\1").is_err());
+        assert!(translate(r"(?<n>a)\k<n>").is_err());
+        assert!(translate("a\\").is_err());
+    }
+
+    #[test]
+    fn check_warns_of_unsupported() {
+        let schema = serde_json::from_value::<Schema>(serde_json::json!({
+            "type": "object",
+            "properties": {
+                "a": {"type": "string", "pattern": "^(?!admin)"},
+                "b": {"type": "string", "pattern": "^[a-z]+$"}
+            }
+        }))
+        .unwrap();
+        let warnings = check(&OpenAPI::default(), &schema, "body").unwrap();
+        assert_eq!(
+            warnings,
+            ["The pattern of body.a is not checked: Lookaround assertions are not supported"]
+        );
+
+        let schema = serde_json::from_value::<Schema>(
+            serde_json::json!({"type": "string", "pattern": "[a"}),
+        )
+        .unwrap();
+        assert!(check(&OpenAPI::default(), &schema, "body").is_err());
+    }
+
+    #[test]
+    fn unsupported_patterns_are_not_checked() {
+        use crate::prompts::string::PromptOption;
+        use promptuity::Validator;
+
+        let option = PromptOption::from(openapiv3::StringType {
+            pattern: Some("^(a)\\1$".to_owned()),
+            ..Default::default()
+        });
+        assert!(!option.checks_pattern());
+        assert!(option.validate(&"ab".to_owned()).is_ok());
+    }
+}
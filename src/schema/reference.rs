@@ -1,5 +1,7 @@
 use indexmap::IndexMap;
-use openapiv3::{OpenAPI, Parameter, PathItem, ReferenceOr, RequestBody, Response, Schema};
+use openapiv3::{
+    Example, OpenAPI, Parameter, PathItem, ReferenceOr, RequestBody, Response, Schema,
+};
 use serde::de::DeserializeOwned;
 
 use crate::schema::error::SchemaError;
@@ -63,6 +65,12 @@ impl Lookup for Schema {
     }
 }
 
+impl Lookup for Example {
+    fn lookup(api: &OpenAPI) -> Option<&IndexMap<String, ReferenceOr<Self>>> {
+        api.components.as_ref().map(|x| &x.examples)
+    }
+}
+
 impl Lookup for PathItem {
     fn lookup(api: &OpenAPI) -> Option<&IndexMap<String, ReferenceOr<Self>>> {
         Some(&api.paths.paths)
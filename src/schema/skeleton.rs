@@ -0,0 +1,210 @@
+use openapiv3::{OpenAPI, Schema, SchemaKind, Type};
+use serde_json::{json, Map, Value};
+
+use crate::{
+    prompts::utils::escape_strings,
+    schema::{error::SchemaError, reference::ReferenceOrExt},
+};
+
+/// Required references nested deeper than this are left `null`, so recursive schemas end.
+const MAX_DEPTH: usize = 16;
+
+/// A value to start editing from: the required properties filled with the
+/// default, the example, the first enum value or an empty value of the type.
+/// The values of `seed` are kept, and completed with the required properties they miss.
+/// `escape` is applied to the strings taken from the schema.
+pub fn skeleton(
+    api: &OpenAPI,
+    schema: &Schema,
+    seed: Option<&Value>,
+    escape: Option<fn(&str) -> String>,
+) -> Result<Value, SchemaError> {
+    Skeleton { api, escape }.build(schema, seed, 0)
+}
+
+struct Skeleton<'a> {
+    api: &'a OpenAPI,
+    escape: Option<fn(&str) -> String>,
+}
+
+impl Skeleton<'_> {
+    fn build(
+        &self,
+        schema: &Schema,
+        seed: Option<&Value>,
+        depth: usize,
+    ) -> Result<Value, SchemaError> {
+        let api = self.api;
+        if depth > MAX_DEPTH {
+            return Ok(Value::Null);
+        }
+
+        match (&schema.schema_kind, seed) {
+            (SchemaKind::Type(Type::Object(object)), Some(Value::Object(seed))) => {
+                let mut map = Map::new();
+                for (key, property) in &object.properties {
+                    let property = property.clone().unbox();
+                    let property = property.item(api)?;
+                    match seed.get(key) {
+                        Some(value) => {
+                            map.insert(key.clone(), self.build(property, Some(value), depth + 1)?);
+                        }
+                        None if object.required.contains(key)
+                            && !property.schema_data.read_only =>
+                        {
+                            map.insert(key.clone(), self.build(property, None, depth + 1)?);
+                        }
+                        None => {}
+                    }
+                }
+                for (key, value) in seed {
+                    if !map.contains_key(key) {
+                        map.insert(key.clone(), value.clone());
+                    }
+                }
+                Ok(Value::Object(map))
+            }
+            (SchemaKind::Type(Type::Array(array)), Some(Value::Array(seed))) => {
+                let Some(items) = &array.items else {
+                    return Ok(json!(seed));
+                };
+                let items = items.clone().unbox();
+                let items = items.item(api)?;
+                seed.iter()
+                    .map(|x| self.build(items, Some(x), depth + 1))
+                    .collect::<Result<Vec<_>, _>>()
+                    .map(Value::Array)
+            }
+            (_, Some(seed)) => Ok(seed.clone()),
+            (_, None) => self.empty(schema, depth),
+        }
+    }
+
+    fn empty(&self, schema: &Schema, depth: usize) -> Result<Value, SchemaError> {
+        let api = self.api;
+        let data = &schema.schema_data;
+        if let Some(value) = data.default.as_ref().or(data.example.as_ref()) {
+            return self.build(schema, Some(&escape_strings(value, self.escape)), depth);
+        }
+
+        Ok(match &schema.schema_kind {
+            SchemaKind::Type(Type::String(x)) => match x.enumeration.iter().flatten().next() {
+                Some(value) => escape_strings(&json!(value), self.escape),
+                None => json!(""),
+            },
+            SchemaKind::Type(Type::Number(x)) => match x.enumeration.iter().flatten().next() {
+                Some(value) => json!(value),
+                None => json!(x.minimum.unwrap_or(0.0)),
+            },
+            SchemaKind::Type(Type::Integer(x)) => match x.enumeration.iter().flatten().next() {
+                Some(value) => json!(value),
+                None => json!(x.minimum.unwrap_or(0)),
+            },
+            SchemaKind::Type(Type::Boolean(_)) => json!(false),
+            SchemaKind::Type(Type::Object(_)) => self.build(schema, Some(&json!({})), depth)?,
+            // One item shows the shape of the items, and is removed or copied.
+            SchemaKind::Type(Type::Array(array)) => match &array.items {
+                Some(items) => {
+                    let items = items.clone().unbox();
+                    let items = items.item(api)?;
+                    let item = self.build(items, None, depth + 1)?;
+                    json!(vec![item; array.min_items.unwrap_or(1).max(1)])
+                }
+                None => json!([]),
+            },
+            _ => Value::Null,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schema(value: Value) -> Schema {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn required_properties_are_filled() {
+        let schema = schema(json!({
+            "type": "object",
+            "required": ["name", "kind", "tags", "id", "owner"],
+            "properties": {
+                "id": {"type": "integer", "readOnly": true},
+                "name": {"type": "string"},
+                "kind": {"type": "string", "enum": ["cat", "dog"]},
+                "age": {"type": "integer", "minimum": 1},
+                "tags": {"type": "array", "minItems": 2, "items": {"type": "string", "default": "a"}},
+                "owner": {
+                    "type": "object",
+                    "required": ["email"],
+                    "properties": {"email": {"type": "string", "example": "ann@example.com"}}
+                }
+            }
+        }));
+        assert_eq!(
+            skeleton(&OpenAPI::default(), &schema, None, None).unwrap(),
+            json!({
+                "name": "",
+                "kind": "cat",
+                "tags": ["a", "a"],
+                "owner": {"email": "ann@example.com"}
+            })
+        );
+    }
+
+    #[test]
+    fn seed_is_completed() {
+        let schema = schema(json!({
+            "type": "object",
+            "required": ["name", "count"],
+            "properties": {
+                "name": {"type": "string", "default": "${USER}"},
+                "count": {"type": "integer", "minimum": 1},
+                "items": {
+                    "type": "array",
+                    "items": {"type": "object", "required": ["qty"], "properties": {"qty": {"type": "integer"}}}
+                }
+            }
+        }));
+        let seed = json!({"count": 3, "items": [{}, {"qty": 2}], "extra": true});
+        let escape = |x: &str| x.replace('$', "\\$");
+        assert_eq!(
+            skeleton(&OpenAPI::default(), &schema, Some(&seed), Some(escape)).unwrap(),
+            json!({
+                "name": "\\${USER}",
+                "count": 3,
+                "items": [{"qty": 0}, {"qty": 2}],
+                "extra": true
+            })
+        );
+    }
+
+    #[test]
+    fn recursive_schemas_end() {
+        let api = serde_json::from_value::<OpenAPI>(json!({
+            "openapi": "3.0.0",
+            "info": {"title": "", "version": ""},
+            "paths": {},
+            "components": {"schemas": {"Node": {
+                "type": "object",
+                "required": ["next"],
+                "properties": {"next": {"$ref": "#/components/schemas/Node"}}
+            }}}
+        }))
+        .unwrap();
+        let schema = api.components.as_ref().unwrap().schemas["Node"]
+            .item(&api)
+            .unwrap()
+            .clone();
+        let mut value = skeleton(&api, &schema, None, None).unwrap();
+        let mut depth = 0;
+        while let Some(next) = value.get("next") {
+            value = next.clone();
+            depth += 1;
+        }
+        assert_eq!(value, Value::Null);
+        assert!(depth > MAX_DEPTH / 2 && depth <= MAX_DEPTH + 1, "{depth}");
+    }
+}
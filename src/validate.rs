@@ -0,0 +1,462 @@
+use std::fmt::Display;
+
+use indexmap::IndexMap;
+use openapiv3::{AdditionalProperties, OpenAPI, ReferenceOr, Schema, SchemaKind, Type};
+use promptuity::Validator;
+use serde_json::Value;
+
+use crate::{
+    prompts::{array, number, object, string},
+    schema::{error::SchemaError, reference::ReferenceOrExt},
+};
+
+/// A value which does not satisfy the schema.
+#[derive(Debug, Clone)]
+pub struct Violation {
+    pub path: String,
+    pub message: String,
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+impl Violation {
+    fn new(path: &str, message: impl Display) -> Self {
+        Self {
+            path: path.to_owned(),
+            message: message.to_string(),
+        }
+    }
+
+    /// The keywords named at the end of the message, e.g. `minimum` and `maximum`
+    /// of `(minimum, maximum)`, or `format` of `(format: int64)`.
+    pub fn keywords(&self) -> Vec<&str> {
+        self.message
+            .strip_suffix(')')
+            .and_then(|x| x.rsplit_once('('))
+            .map(|(_, keywords)| {
+                keywords
+                    .split(", ")
+                    .map(|x| x.split(':').next().unwrap_or(x))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// Parses text given on the command line as JSON, falling back to a string.
+/// A number which would not be written back as typed, e.g. `1.10` or `1e2`,
+/// is kept as text, so [`coerce`] gives a string schema the text unchanged.
+/// Elsewhere it is a number again.
+pub fn parse_text(text: &str) -> Value {
+    match text.parse::<Value>() {
+        Ok(Value::Number(n)) if n.to_string() != text => Value::String(text.to_owned()),
+        Ok(value) => value,
+        Err(_) => Value::String(text.to_owned()),
+    }
+}
+
+/// Converts scalars given as text (e.g. from the command line) to strings
+/// where the schema expects a string, numbers kept as text by [`parse_text`] to numbers
+/// where the schema expects a number, and single values to one item arrays
+/// where the schema expects an array.
+pub fn coerce(api: &OpenAPI, schema: &Schema, value: Value) -> Result<Value, SchemaError> {
+    Ok(match (&schema.schema_kind, value) {
+        (SchemaKind::Type(Type::String(_)), value @ (Value::Number(_) | Value::Bool(_))) => {
+            Value::String(value.to_string())
+        }
+        (SchemaKind::Type(Type::Number(_) | Type::Integer(_)), Value::String(text)) => {
+            match text.parse::<serde_json::Number>() {
+                Ok(number) => Value::Number(number),
+                Err(_) => Value::String(text),
+            }
+        }
+        (SchemaKind::Type(Type::Object(object)), Value::Object(map)) => {
+            let mut result = serde_json::Map::new();
+            for (key, value) in map {
+                let value = match (object.properties.get(&key), &object.additional_properties) {
+                    (Some(property), _) => coerce(api, property.clone().unbox().item(api)?, value)?,
+                    (None, Some(AdditionalProperties::Schema(schema))) => {
+                        coerce(api, schema.item(api)?, value)?
+                    }
+                    (None, _) => untyped(value),
+                };
+                result.insert(key, value);
+            }
+            Value::Object(result)
+        }
+        (SchemaKind::Type(Type::Array(array)), Value::Array(items)) => match &array.items {
+            Some(item) => {
+                let item = item.clone().unbox();
+                let item = item.item(api)?;
+                Value::Array(
+                    items
+                        .into_iter()
+                        .map(|x| coerce(api, item, x))
+                        .collect::<Result<_, _>>()?,
+                )
+            }
+            None => untyped(Value::Array(items)),
+        },
+        (SchemaKind::Type(Type::Array(array)), value) if !value.is_null() => {
+            let value = match &array.items {
+                Some(item) => coerce(api, item.clone().unbox().item(api)?, value)?,
+                None => untyped(value),
+            };
+            Value::Array(vec![value])
+        }
+        (SchemaKind::Any(_), value) => untyped(value),
+        (_, value) => value,
+    })
+}
+
+/// Turns the numbers kept as text by [`parse_text`] back to numbers where no schema tells the type.
+fn untyped(value: Value) -> Value {
+    match value {
+        Value::String(text) => match text.parse::<serde_json::Number>() {
+            Ok(number) if number.to_string() != text => Value::Number(number),
+            _ => Value::String(text),
+        },
+        Value::Array(items) => Value::Array(items.into_iter().map(untyped).collect()),
+        Value::Object(map) => {
+            Value::Object(map.into_iter().map(|(k, v)| (k, untyped(v))).collect())
+        }
+        value => value,
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Mode {
+    /// Missing required properties are not reported.
+    partial: bool,
+}
+
+/// Validates the request value against the schema and returns every violation found.
+/// `path` names the value in the messages. `readOnly` properties are not required.
+pub fn validate(
+    api: &OpenAPI,
+    schema: &Schema,
+    value: &Value,
+    path: &str,
+) -> Result<Vec<Violation>, SchemaError> {
+    let mode = Mode { partial: false };
+    check(api, schema, value, path, mode)
+}
+
+/// Same as [`validate`], but missing required properties are not reported,
+/// so this fits values which are completed by the prompts later.
+pub fn validate_partial(
+    api: &OpenAPI,
+    schema: &Schema,
+    value: &Value,
+    path: &str,
+) -> Result<Vec<Violation>, SchemaError> {
+    let mode = Mode { partial: true };
+    check(api, schema, value, path, mode)
+}
+
+fn check(
+    api: &OpenAPI,
+    schema: &Schema,
+    value: &Value,
+    path: &str,
+    mode: Mode,
+) -> Result<Vec<Violation>, SchemaError> {
+    let mut violations = Vec::new();
+
+    if value.is_null() && matches!(schema.schema_kind, SchemaKind::Type(_)) {
+        if !schema.schema_data.nullable {
+            violations.push(Violation::new(path, "Value must not be null (nullable)"));
+        }
+        return Ok(violations);
+    }
+
+    match &schema.schema_kind {
+        SchemaKind::Type(Type::Boolean(_)) if !value.is_boolean() => {
+            violations.push(Violation::new(path, "Value is not a boolean (type)"));
+        }
+        SchemaKind::Type(Type::String(string)) => match value.as_str() {
+            Some(s) => {
+                let option = string::PromptOption::from(string.clone());
+                if let Err(err) = option.validate(&s.to_owned()) {
+                    violations.push(Violation::new(path, err));
+                }
+            }
+            None => violations.push(Violation::new(path, "Value is not a string (type)")),
+        },
+        SchemaKind::Type(Type::Number(number)) => match value {
+            Value::Number(n) => {
+                let option = number::PromptOption::<f64>::from(number.clone());
+                if let Err(err) = option.validate(&n.to_string()) {
+                    violations.push(Violation::new(path, err));
+                }
+            }
+            _ => violations.push(Violation::new(path, "Value is not a number (type)")),
+        },
+        SchemaKind::Type(Type::Integer(integer)) => match integer_text(value) {
+            Some(n) => {
+                let option = number::PromptOption::<i128>::from(integer.clone());
+                if let Err(err) = option.validate(&n) {
+                    violations.push(Violation::new(path, err));
+                }
+            }
+            None => violations.push(Violation::new(path, "Value is not an integer (type)")),
+        },
+        SchemaKind::Type(Type::Object(object)) => match value.as_object() {
+            Some(map) => {
+                for key in object.required.iter().filter(|_| !mode.partial) {
+                    let excluded = match object.properties.get(key) {
+                        Some(property) => {
+                            let property = property.clone().unbox();
+                            property.item(api)?.schema_data.read_only
+                        }
+                        None => false,
+                    };
+                    if !excluded && !map.contains_key(key) {
+                        violations.push(Violation::new(
+                            &format!("{}.{}", path, key),
+                            "Value is required (required)",
+                        ));
+                    }
+                }
+                let mut option = object::PromptOption::new(object.clone());
+                option.with_property_names(object::property_names(schema));
+                let result = if mode.partial {
+                    option.validate_keys(map)
+                } else {
+                    option.validate(map)
+                };
+                if let Err(err) = result {
+                    violations.push(Violation::new(path, err));
+                }
+                for (key, value) in map {
+                    let property = match (object.properties.get(key), &object.additional_properties)
+                    {
+                        (Some(property), _) => property.clone().unbox(),
+                        (None, Some(AdditionalProperties::Schema(schema))) => *schema.clone(),
+                        (None, _) => continue,
+                    };
+                    let property = property.item(api)?;
+                    violations.extend(check(
+                        api,
+                        property,
+                        value,
+                        &format!("{}.{}", path, key),
+                        mode,
+                    )?);
+                }
+            }
+            None => violations.push(Violation::new(path, "Value is not an object (type)")),
+        },
+        SchemaKind::Type(Type::Array(array)) => match value.as_array() {
+            Some(items) => {
+                let option = array::PromptOption::new(array.clone());
+                if let Err(err) = option.validate(items) {
+                    violations.push(Violation::new(path, err));
+                }
+                if let Some(item) = &array.items {
+                    let item = item.clone().unbox();
+                    let item = item.item(api)?;
+                    for (idx, value) in items.iter().enumerate() {
+                        violations.extend(check(
+                            api,
+                            item,
+                            value,
+                            &format!("{}[{}]", path, idx),
+                            mode,
+                        )?);
+                    }
+                }
+            }
+            None => violations.push(Violation::new(path, "Value is not an array (type)")),
+        },
+        _ => {}
+    }
+
+    Ok(violations)
+}
+
+/// The digits of an integer. Integers beyond `u64` are read as floats by `serde_json`,
+/// and are still integers, so their range is checked instead of their type.
+fn integer_text(value: &Value) -> Option<String> {
+    match (value.as_i64(), value.as_u64(), value.as_f64()) {
+        (Some(x), _, _) => Some(x.to_string()),
+        (_, Some(x), _) => Some(x.to_string()),
+        (_, _, Some(x)) if x.is_finite() && x.fract() == 0.0 => Some(format!("{:.0}", x)),
+        _ => None,
+    }
+}
+
+/// Reports the `readOnly` properties set in the request value, which the
+/// server ignores or rejects.
+pub fn read_only(
+    api: &OpenAPI,
+    schema: &Schema,
+    value: &Value,
+    path: &str,
+) -> Result<Vec<Violation>, SchemaError> {
+    let mut violations = Vec::new();
+    match (&schema.schema_kind, value) {
+        (SchemaKind::Type(Type::Object(object)), Value::Object(map)) => {
+            for (key, value) in map {
+                let Some(property) = object.properties.get(key) else {
+                    continue;
+                };
+                let property = property.clone().unbox();
+                let property = property.item(api)?;
+                let path = format!("{}.{}", path, key);
+                if property.schema_data.read_only {
+                    violations.push(Violation::new(&path, "Value is readOnly (readOnly)"));
+                } else {
+                    violations.extend(read_only(api, property, value, &path)?);
+                }
+            }
+        }
+        (SchemaKind::Type(Type::Array(array)), Value::Array(items)) => {
+            if let Some(item) = &array.items {
+                let item = item.clone().unbox();
+                let item = item.item(api)?;
+                for (idx, value) in items.iter().enumerate() {
+                    violations.extend(read_only(api, item, value, &format!("{}[{}]", path, idx))?);
+                }
+            }
+        }
+        _ => {}
+    }
+
+    Ok(violations)
+}
+
+/// Validates the given fields against their property schemas, as [`validate_partial`] does.
+pub fn validate_properties<'a>(
+    api: &OpenAPI,
+    properties: &IndexMap<String, ReferenceOr<Box<Schema>>>,
+    values: impl IntoIterator<Item = (&'a String, &'a Value)>,
+    path: &str,
+) -> Result<Vec<Violation>, SchemaError> {
+    let mut violations = Vec::new();
+    for (key, value) in values {
+        if let Some(property) = properties.get(key) {
+            let property = property.clone().unbox();
+            let property = property.item(api)?;
+            violations.extend(validate_partial(
+                api,
+                property,
+                value,
+                &format!("{}.{}", path, key),
+            )?);
+        }
+    }
+
+    Ok(violations)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn schema(value: Value) -> Schema {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn parse_text_keeps_numbers_as_typed() {
+        assert_eq!(parse_text("10"), json!(10));
+        assert_eq!(parse_text("1.5"), json!(1.5));
+        assert_eq!(parse_text("true"), json!(true));
+        assert_eq!(parse_text("1.10"), json!("1.10"));
+        assert_eq!(parse_text("1e2"), json!("1e2"));
+        assert_eq!(parse_text("abc"), json!("abc"));
+    }
+
+    #[test]
+    fn coerce_flag_text() {
+        let api = OpenAPI::default();
+        let string = schema(json!({"type": "string"}));
+        let number = schema(json!({"type": "number"}));
+        let integer = schema(json!({"type": "integer"}));
+        let coerce = |schema, text| coerce(&api, schema, parse_text(text)).unwrap();
+
+        assert_eq!(coerce(&string, "1.10"), json!("1.10"));
+        assert_eq!(coerce(&string, "1e2"), json!("1e2"));
+        assert_eq!(coerce(&string, "10"), json!("10"));
+        assert_eq!(coerce(&number, "1.10"), json!(1.1));
+        assert_eq!(coerce(&integer, "1e2"), json!(100.0));
+        assert_eq!(coerce(&integer, "abc"), json!("abc"));
+
+        let free = schema(json!({"type": "object", "properties": {"a": {"type": "string"}}}));
+        let value = json!({"a": "2.50", "b": "2.50", "c": "10"});
+        assert_eq!(
+            super::coerce(&api, &free, value).unwrap(),
+            json!({"a": "2.50", "b": 2.5, "c": "10"})
+        );
+    }
+
+    fn messages(violations: Vec<Violation>) -> Vec<String> {
+        violations.into_iter().map(|x| x.to_string()).collect()
+    }
+
+    #[test]
+    fn violations_of_a_body() {
+        let api = OpenAPI::default();
+        let schema = schema(json!({
+            "type": "object",
+            "required": ["name", "id"],
+            "properties": {
+                "id": {"type": "integer", "readOnly": true},
+                "name": {"type": "string", "minLength": 2},
+                "age": {"type": "integer", "minimum": 0},
+                "tags": {"type": "array", "maxItems": 1, "items": {"type": "string"}},
+                "nick": {"type": "string", "nullable": true}
+            }
+        }));
+        let value = json!({"age": -1, "tags": ["a", 1], "nick": null});
+
+        assert_eq!(
+            messages(validate(&api, &schema, &value, "body").unwrap()),
+            [
+                "body.name: Value is required (required)",
+                "body.age: Value must be greater than or equal to 0 (minimum)",
+                "body.tags: Array must have at most 1 items (maxItems)",
+                "body.tags[1]: Value is not a string (type)",
+            ]
+        );
+        assert_eq!(
+            messages(validate_partial(&api, &schema, &value, "body").unwrap()),
+            [
+                "body.age: Value must be greater than or equal to 0 (minimum)",
+                "body.tags: Array must have at most 1 items (maxItems)",
+                "body.tags[1]: Value is not a string (type)",
+            ]
+        );
+        let valid = json!({"name": "Tama", "nick": null});
+        assert!(validate(&api, &schema, &valid, "body").unwrap().is_empty());
+        assert_eq!(
+            messages(read_only(&api, &schema, &json!({"id": 1}), "body").unwrap()),
+            ["body.id: Value is readOnly (readOnly)"]
+        );
+    }
+
+    #[test]
+    fn violation_keywords() {
+        let violation = |message: &str| Violation::new("body", message);
+        assert_eq!(
+            violation("Value is too long. Maximum length is 5 (maxLength)").keywords(),
+            ["maxLength"]
+        );
+        assert_eq!(
+            violation("Value must be between 0 and 255 (format: uint8)").keywords(),
+            ["format"]
+        );
+        assert_eq!(
+            violation("Value must be between 1 and 3 (minimum, maximum)").keywords(),
+            ["minimum", "maximum"]
+        );
+        assert!(violation("Value is invalid").keywords().is_empty());
+    }
+}